@@ -0,0 +1,162 @@
+use anyhow::{Context, Result, bail};
+use flate2::read::GzDecoder;
+use std::io::{Cursor, Read};
+use std::path::Path;
+
+/// How a release asset packages the executable.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetKind {
+    Binary,
+    TarGz,
+    Zip,
+}
+
+impl AssetKind {
+    pub fn detect(name: &str) -> Self {
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Self::TarGz
+        } else if name.ends_with(".zip") {
+            Self::Zip
+        } else {
+            Self::Binary
+        }
+    }
+}
+
+/// Returns the executable: the asset itself, or the `binary_name` entry
+/// found at any depth inside an archive.
+pub fn extract_binary(kind: AssetKind, bytes: Vec<u8>, binary_name: &str) -> Result<Vec<u8>> {
+    match kind {
+        AssetKind::Binary => Ok(bytes),
+        AssetKind::TarGz => from_tar_gz(&bytes, binary_name),
+        AssetKind::Zip => from_zip(bytes, binary_name),
+    }
+}
+
+fn from_tar_gz(bytes: &[u8], binary_name: &str) -> Result<Vec<u8>> {
+    let mut archive = tar::Archive::new(GzDecoder::new(bytes));
+    let mut seen = Vec::new();
+    for entry in archive.entries().context("failed to read tar.gz archive")? {
+        let mut entry = entry.context("failed to read tar.gz entry")?;
+        let path = entry
+            .path()
+            .context("invalid path in tar.gz archive")?
+            .to_string_lossy()
+            .to_string();
+        if entry.header().entry_type().is_file() && file_name_is(&path, binary_name) {
+            let mut binary = Vec::new();
+            entry
+                .read_to_end(&mut binary)
+                .with_context(|| format!("failed to extract `{path}` from tar.gz archive"))?;
+            return Ok(binary);
+        }
+        seen.push(path);
+    }
+    bail!(
+        "archive has no `{binary_name}` executable. entries: {}",
+        seen.join(", ")
+    )
+}
+
+fn from_zip(bytes: Vec<u8>, binary_name: &str) -> Result<Vec<u8>> {
+    let mut archive =
+        zip::ZipArchive::new(Cursor::new(bytes)).context("failed to read zip archive")?;
+    let mut seen = Vec::new();
+    for index in 0..archive.len() {
+        let mut file = archive
+            .by_index(index)
+            .context("failed to read zip entry")?;
+        let path = file.name().to_string();
+        if file.is_file() && file_name_is(&path, binary_name) {
+            let mut binary = Vec::new();
+            file.read_to_end(&mut binary)
+                .with_context(|| format!("failed to extract `{path}` from zip archive"))?;
+            return Ok(binary);
+        }
+        seen.push(path);
+    }
+    bail!(
+        "archive has no `{binary_name}` executable. entries: {}",
+        seen.join(", ")
+    )
+}
+
+fn file_name_is(path: &str, name: &str) -> bool {
+    Path::new(path)
+        .file_name()
+        .is_some_and(|file_name| file_name == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn tar_gz(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, data) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (path, data) in entries {
+            writer
+                .start_file(*path, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn detects_the_asset_kind_from_its_name() {
+        assert_eq!(AssetKind::detect("wgit-linux.tar.gz"), AssetKind::TarGz);
+        assert_eq!(AssetKind::detect("wgit-linux.tgz"), AssetKind::TarGz);
+        assert_eq!(AssetKind::detect("wgit-windows.zip"), AssetKind::Zip);
+        assert_eq!(AssetKind::detect("wgit-windows.exe"), AssetKind::Binary);
+    }
+
+    #[test]
+    fn a_bare_binary_is_returned_as_is() {
+        let bytes = extract_binary(AssetKind::Binary, b"elf".to_vec(), "wgit").unwrap();
+        assert_eq!(bytes, b"elf");
+    }
+
+    #[test]
+    fn extracts_the_binary_from_a_nested_tar_gz_entry() {
+        let archive = tar_gz(&[
+            ("wgit-1.0/README.md", b"readme"),
+            ("wgit-1.0/bin/wgit-helper", b"helper"),
+            ("wgit-1.0/bin/wgit", b"elf"),
+        ]);
+        let bytes = extract_binary(AssetKind::TarGz, archive, "wgit").unwrap();
+        assert_eq!(bytes, b"elf");
+    }
+
+    #[test]
+    fn extracts_the_binary_from_a_zip() {
+        let archive = zip(&[("LICENSE", b"mit"), ("dist/wgit.exe", b"pe")]);
+        let bytes = extract_binary(AssetKind::Zip, archive, "wgit.exe").unwrap();
+        assert_eq!(bytes, b"pe");
+    }
+
+    #[test]
+    fn a_missing_binary_lists_the_entries() {
+        let archive = tar_gz(&[("wgit-1.0/README.md", b"readme")]);
+        let error = extract_binary(AssetKind::TarGz, archive, "wgit").unwrap_err();
+        assert!(error.to_string().contains("entries: wgit-1.0/README.md"));
+
+        let archive = zip(&[("docs/wgit.txt", b"text")]);
+        let error = extract_binary(AssetKind::Zip, archive, "wgit.exe").unwrap_err();
+        assert!(error.to_string().contains("entries: docs/wgit.txt"));
+    }
+}
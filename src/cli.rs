@@ -11,15 +11,29 @@ pub struct Cli {
 pub enum Command {
     /// Initialize wgit in the current repository.
     Init,
+    /// Show branch, upstream, changes, stashes and warnings in one dashboard.
+    Status {
+        /// Print a plain-text summary instead of opening the dashboard.
+        #[arg(long)]
+        short: bool,
+    },
     /// Stage files with a multi-select interface.
     Add,
     /// Commit changes with guided prompts.
-    Commit,
+    Commit {
+        /// Reopen the last commit in the editor and replace it (also rewords it).
+        #[arg(long)]
+        amend: bool,
+    },
+    /// Record staged changes as a fixup for an earlier branch commit, then autosquash.
+    Fixup,
     /// Delete a local branch with safety checks.
     Delete,
-    /// Create a new branch from the current branch.
+    /// Clean up merged, squash-merged, gone and stale branches in one batch.
+    Prune,
+    /// Create a new branch from the current branch (release and hotfix follow `[flow]`).
     Start,
-    /// Finish current branch and merge into parent.
+    /// Finish current branch and merge into parent (release and hotfix follow `[flow]`).
     Finish,
     /// After resolving conflicts: check markers, stage all, then commit with guided message.
     Solve,
@@ -30,13 +44,105 @@ pub enum Command {
     /// Roll back with selected strategy and level.
     Undo,
     /// Pull and push with assisted flow.
-    Sync,
+    Sync {
+        /// Only fetch and report incoming/outgoing commits.
+        #[arg(long, conflicts_with = "all_branches")]
+        fetch_only: bool,
+        /// Fast-forward every local branch that tracks a remote branch.
+        #[arg(long)]
+        all_branches: bool,
+    },
+    /// Cherry-pick commits onto new backport branches for other release lines.
+    Backport,
+    /// Reorder, squash, drop and reword branch commits before finishing.
+    Tidy,
+    /// Bring parent branch updates into the current branch.
+    Refresh,
+    /// Browse, apply and manage stash entries.
+    Stash,
+    /// Publish tagged releases.
+    Release {
+        #[command(subcommand)]
+        action: ReleaseAction,
+    },
+    /// Browse and edit settings; `show`, `get`, `set` and `unset` for scripts.
+    Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
     /// Update wgit to the latest release.
-    Update,
+    Update {
+        /// Release channel; overrides `update.channel`.
+        #[arg(long, value_parser = ["stable", "prerelease"])]
+        channel: Option<String>,
+        /// Install a specific release, e.g. v1.2.3 (downgrades are allowed).
+        #[arg(long, conflicts_with = "channel")]
+        version: Option<String>,
+        /// Swap back to the binary kept by the previous update.
+        #[arg(long, conflicts_with_all = ["channel", "version", "check"])]
+        rollback: bool,
+        /// Only report whether an update is available; exits with 10 when one is
+        /// (0 when up to date, 1 on errors, 2 on usage errors).
+        #[arg(long)]
+        check: bool,
+        /// Refresh the cached update notice quietly (spawned by wgit itself).
+        #[arg(long, hide = true, conflicts_with_all = ["channel", "version", "rollback", "check"])]
+        background_check: bool,
+    },
     /// Open command menu.
     Menu,
 }
 
+#[derive(Clone, Debug, Subcommand)]
+pub enum ConfigAction {
+    /// Print every effective setting.
+    Show {
+        /// Also print the file each value came from.
+        #[arg(long)]
+        origin: bool,
+    },
+    /// Print the effective value of a setting, or every value under a table.
+    Get {
+        /// Dotted key, e.g. `sync.refresh_strategy` or `safety.rules."release/*"`.
+        key: String,
+    },
+    /// Write a setting to the local config (or the global or team file).
+    Set {
+        key: String,
+        /// Lists take `a, b` or `["a", "b"]`.
+        value: String,
+        /// Write `~/.config/wgit/config.toml` instead.
+        #[arg(long, conflicts_with = "team")]
+        global: bool,
+        /// Write the committed `.wgit.toml` instead.
+        #[arg(long)]
+        team: bool,
+    },
+    /// Remove a setting from the local config (or the global or team file).
+    Unset {
+        key: String,
+        #[arg(long, conflicts_with = "team")]
+        global: bool,
+        #[arg(long)]
+        team: bool,
+    },
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum ReleaseAction {
+    /// Push a tag and create a GitHub release with notes, assets and SHA256SUMS.
+    Publish {
+        /// Tag to publish. Defaults to the latest tag reachable from HEAD.
+        tag: Option<String>,
+        /// Create the release as an unpublished draft.
+        #[arg(long)]
+        draft: bool,
+        /// Mark the release as a pre-release.
+        #[arg(long)]
+        prerelease: bool,
+    },
+}
+
 pub fn parse() -> Cli {
     Cli::parse()
 }
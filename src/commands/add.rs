@@ -1,4 +1,5 @@
-use crate::{config, git, utils};
+use crate::policy::{self, Action};
+use crate::{git, utils};
 use anyhow::Result;
 use std::path::Path;
 
@@ -6,11 +7,9 @@ pub fn run() -> Result<()> {
     let cwd = Path::new(".");
     println!("Add workflow: verify branch policy, select changed files, then stage selection.");
     let current_branch = git::current_branch(cwd)?;
-    if config::is_protected_branch(cwd, &current_branch)? {
-        println!(
-            "Add is not allowed on protected branch `{}`.",
-            current_branch.trim()
-        );
+    if let Some(reason) = policy::evaluate(cwd, &current_branch)?.denies(Action::Commit) {
+        println!("{reason}");
+        println!("Create a work branch with `wgit start` first.");
         return Ok(());
     }
 
@@ -0,0 +1,344 @@
+use crate::config::{self, WgitConfig};
+use crate::git::CommitEntry;
+use crate::{convention, git, github, policy, utils};
+use anyhow::Result;
+use std::path::Path;
+
+const RECENT_COMMIT_LIMIT: &str = "30";
+
+/// A branch that can receive a backport: local, or only on a remote.
+struct Target {
+    /// Branch name without remote, used as PR base and recorded parent.
+    name: String,
+    /// Ref to branch from (`release/1.2` or `origin/release/1.2`).
+    start_point: String,
+}
+
+enum Outcome {
+    Picked,
+    Conflict,
+    Skipped(String),
+}
+
+struct BackportResult {
+    target: String,
+    start_point: String,
+    branch: String,
+    outcome: Outcome,
+}
+
+pub fn run() -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Backport workflow: pick commits, pick target branches, then cherry-pick (-x) onto one backport branch per target."
+    );
+    if git::rebase_in_progress(cwd) {
+        println!("A rebase is in progress. Finish or abort it before backporting.");
+        return Ok(());
+    }
+    if !git::is_clean_worktree(cwd)? {
+        println!("Please commit or stash your changes before running backport.");
+        return Ok(());
+    }
+    let cfg = config::load_config(cwd)?;
+    let remotes = git::list_remotes(cwd)?;
+    if !remotes.is_empty() {
+        git::fetch_all(cwd)?;
+    }
+
+    let original = git::current_branch(cwd)?;
+    let locals = git::list_tracking_branches(cwd)?;
+    let mut source_names: Vec<String> = locals.iter().map(|branch| branch.name.clone()).collect();
+    if let Some(idx) = source_names.iter().position(|name| *name == original) {
+        let current = source_names.remove(idx);
+        source_names.insert(0, current);
+    }
+    let Some(source_idx) = utils::select_one(
+        "Select source branch (the hotfix branch, or the branch it was merged into)",
+        &source_names,
+    )?
+    else {
+        println!("Backport canceled.");
+        return Ok(());
+    };
+    let source = source_names[source_idx].clone();
+
+    let (commits, from_branch_range) = source_commits(cwd, &source)?;
+    if commits.is_empty() {
+        println!("No commits found on `{source}`.");
+        return Ok(());
+    }
+    let labels: Vec<String> = commits
+        .iter()
+        .map(|commit| format!("{} {}", short(&commit.hash), commit.subject))
+        .collect();
+    let preselected: Vec<usize> = if from_branch_range {
+        (0..commits.len()).collect()
+    } else {
+        Vec::new()
+    };
+    let picked = utils::select_many_preselected(
+        "Select commits to backport (applied oldest first)",
+        &labels,
+        &preselected,
+    )?;
+    if picked.is_empty() {
+        println!("Backport canceled. No commits selected.");
+        return Ok(());
+    }
+    let picked: Vec<&CommitEntry> = picked.iter().map(|idx| &commits[*idx]).collect();
+
+    let targets = target_candidates(cwd, &source)?;
+    if targets.is_empty() {
+        println!("No other branches to backport to.");
+        return Ok(());
+    }
+    let target_labels: Vec<String> = targets
+        .iter()
+        .map(|target| {
+            if target.start_point == target.name {
+                target.name.clone()
+            } else {
+                format!("{} (remote only: {})", target.name, target.start_point)
+            }
+        })
+        .collect();
+    let preselected: Vec<usize> = targets
+        .iter()
+        .enumerate()
+        .filter(|(_, target)| is_default_target(&cfg, &target.name))
+        .map(|(idx, _)| idx)
+        .collect();
+    let selected_targets = utils::select_many_preselected(
+        "Select target branches (backport.targets are pre-selected)",
+        &target_labels,
+        &preselected,
+    )?;
+    if selected_targets.is_empty() {
+        println!("Backport canceled. No target branches selected.");
+        return Ok(());
+    }
+
+    let label = if from_branch_range {
+        let name = source.rsplit('/').next().unwrap_or(&source);
+        convention::slugify(name)
+    } else {
+        short(&picked[0].hash).to_string()
+    };
+    let plan: Vec<(&Target, String)> = selected_targets
+        .iter()
+        .map(|idx| {
+            let target = &targets[*idx];
+            let branch = format!(
+                "{}/{label}-to-{}",
+                cfg.backport.branch_prefix,
+                target.name.replace('/', "-")
+            );
+            (target, branch)
+        })
+        .collect();
+
+    println!();
+    println!("Backport plan:");
+    for commit in &picked {
+        println!("  commit {} {}", short(&commit.hash), commit.subject);
+    }
+    for (target, branch) in &plan {
+        println!("  `{}` -> new branch `{branch}`", target.start_point);
+    }
+    println!();
+    if !utils::confirm(&format!(
+        "Create {} backport branch(es) and cherry-pick {} commit(s)?",
+        plan.len(),
+        picked.len()
+    ))? {
+        println!("Backport canceled.");
+        return Ok(());
+    }
+
+    let hashes: Vec<&str> = picked.iter().map(|commit| commit.hash.as_str()).collect();
+    let mut results = Vec::new();
+    for (target, branch) in &plan {
+        let outcome = backport_one(cwd, target, branch, &hashes, &original)?;
+        results.push(BackportResult {
+            target: target.name.clone(),
+            start_point: target.start_point.clone(),
+            branch: branch.clone(),
+            outcome,
+        });
+    }
+
+    println!();
+    println!("Backport report:");
+    for result in &results {
+        match &result.outcome {
+            Outcome::Picked => println!(
+                "  {} -> `{}`: picked {} commit(s)",
+                result.target,
+                result.branch,
+                hashes.len()
+            ),
+            Outcome::Conflict => {
+                println!(
+                    "  {} -> conflict (or changes already present); nothing was created",
+                    result.target
+                );
+                println!(
+                    "    to resolve by hand: git checkout -b {} {} && git cherry-pick -x {}",
+                    result.branch,
+                    result.start_point,
+                    hashes.join(" ")
+                );
+            }
+            Outcome::Skipped(reason) => println!("  {} -> skipped: {reason}", result.target),
+        }
+    }
+
+    let succeeded: Vec<&BackportResult> = results
+        .iter()
+        .filter(|result| matches!(result.outcome, Outcome::Picked))
+        .collect();
+    if succeeded.is_empty() || remotes.is_empty() {
+        return Ok(());
+    }
+    publish(cwd, &cfg, &remotes, &succeeded, &picked, &label)
+}
+
+/// Commits unique to `source` when it has a known parent, otherwise its
+/// recent history (e.g. a hotfix already merged into `main`).
+fn source_commits(cwd: &Path, source: &str) -> Result<(Vec<CommitEntry>, bool)> {
+    let parent = git::parent_branch_for(cwd, source)?;
+    if parent != source {
+        let base = git::branch_upstream(cwd, &parent)?.unwrap_or_else(|| parent.clone());
+        let range = format!("{base}..{source}");
+        let commits = git::list_commit_entries(cwd, &[&range])?;
+        if !commits.is_empty() {
+            return Ok((commits, true));
+        }
+    }
+    let commits = git::list_commit_entries(cwd, &["-n", RECENT_COMMIT_LIMIT, source])?;
+    Ok((commits, false))
+}
+
+fn target_candidates(cwd: &Path, source: &str) -> Result<Vec<Target>> {
+    let locals = git::list_tracking_branches(cwd)?;
+    let mut targets: Vec<Target> = locals
+        .iter()
+        .filter(|branch| branch.name != source)
+        .map(|branch| Target {
+            name: branch.name.clone(),
+            start_point: branch.name.clone(),
+        })
+        .collect();
+    let mut remote_only: Vec<Target> = git::list_remote_branches(cwd)?
+        .into_iter()
+        .filter(|remote| remote.branch != source)
+        .filter(|remote| !locals.iter().any(|local| local.name == remote.branch))
+        .map(|remote| Target {
+            start_point: remote.full_name(),
+            name: remote.branch,
+        })
+        .collect();
+    remote_only.sort_by(|left, right| left.start_point.cmp(&right.start_point));
+    remote_only.dedup_by(|left, right| left.name == right.name);
+    targets.extend(remote_only);
+    Ok(targets)
+}
+
+fn is_default_target(cfg: &WgitConfig, branch: &str) -> bool {
+    cfg.backport
+        .targets
+        .iter()
+        .any(|pattern| pattern == branch || policy::glob_match(pattern, branch))
+}
+
+/// Creates `branch` from the target and cherry-picks `hashes` onto it,
+/// then switches back to `original`.
+fn backport_one(
+    cwd: &Path,
+    target: &Target,
+    branch: &str,
+    hashes: &[&str],
+    original: &str,
+) -> Result<Outcome> {
+    if git::branch_exists(cwd, branch)? {
+        return Ok(Outcome::Skipped(format!(
+            "branch `{branch}` already exists"
+        )));
+    }
+    if !git::is_valid_branch_ref(cwd, branch)? {
+        return Ok(Outcome::Skipped(format!(
+            "`{branch}` is not a valid branch name"
+        )));
+    }
+    git::create_branch_from(cwd, branch, &target.start_point)?;
+    git::record_parent_branch(cwd, branch, &target.name)?;
+    if git::cherry_pick(cwd, hashes)? {
+        git::checkout_branch(cwd, original)?;
+        return Ok(Outcome::Picked);
+    }
+
+    // Leave nothing half-applied: undo the pick and drop the new branch so
+    // the report is the only trace of a failed target.
+    let _ = git::cherry_pick_abort(cwd);
+    git::checkout_branch(cwd, original)?;
+    let _ = git::try_delete_branch(cwd, branch, true);
+    Ok(Outcome::Conflict)
+}
+
+fn publish(
+    cwd: &Path,
+    cfg: &WgitConfig,
+    remotes: &[git::RemoteEntry],
+    succeeded: &[&BackportResult],
+    picked: &[&CommitEntry],
+    label: &str,
+) -> Result<()> {
+    let remote = remotes
+        .iter()
+        .find(|remote| remote.name == "origin")
+        .unwrap_or(&remotes[0])
+        .name
+        .clone();
+    if !utils::confirm(&format!(
+        "Push {} backport branch(es) to `{remote}`?",
+        succeeded.len()
+    ))? {
+        println!("Skipped push. Push later with `git push -u {remote} <branch>`.");
+        return Ok(());
+    }
+    for result in succeeded {
+        git::push_current(cwd, Some(&remote), &result.branch)?;
+    }
+
+    let Some(repo) = github::origin_repo(cwd)? else {
+        return Ok(());
+    };
+    if !utils::confirm("Open a pull request on GitHub for each pushed backport branch?")? {
+        return Ok(());
+    }
+    let mut body = String::from("Backport of:\n\n");
+    for commit in picked {
+        body.push_str(&format!("- {} {}\n", short(&commit.hash), commit.subject));
+    }
+    body.push_str("\nCreated by `wgit backport` with `git cherry-pick -x`.\n");
+    for result in succeeded {
+        let title = format!("Backport {label} to {}", result.target);
+        match github::create_pull_request(
+            &cfg.github_api_base,
+            &repo,
+            &result.branch,
+            &result.target,
+            &title,
+            &body,
+        ) {
+            Ok(url) => println!("  {} -> {url}", result.target),
+            Err(error) => println!("  {} -> pull request failed: {error:#}", result.target),
+        }
+    }
+    Ok(())
+}
+
+fn short(hash: &str) -> &str {
+    &hash[..hash.len().min(7)]
+}
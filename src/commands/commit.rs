@@ -1,57 +1,96 @@
-use crate::{config, git, utils};
+use crate::config::IssuePlacement;
+use crate::policy::{self, Action};
+use crate::utils::{CommitDraft, TrailerKind, TrailerRow};
+use crate::{config, convention, git, trailers, utils};
 use anyhow::{Context, Result, bail};
 use std::fs;
 use std::path::Path;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-pub fn run() -> Result<()> {
+pub fn run(amend: bool) -> Result<()> {
     let cwd = Path::new(".");
-    println!("Commit workflow: validate staged changes, draft message, then create commit.");
-    let current_branch = git::current_branch(cwd)?;
-    if config::is_protected_branch(cwd, &current_branch)? {
+    if amend {
         println!(
-            "Commit is not allowed on protected branch `{}`.",
-            current_branch.trim()
+            "Commit workflow (amend): reopen the last commit message, edit it, then replace the commit."
         );
+    } else {
+        println!("Commit workflow: validate staged changes, draft message, then create commit.");
+    }
+    let current_branch = git::current_branch(cwd)?;
+    let branch_policy = policy::evaluate(cwd, &current_branch)?;
+    if let Some(reason) = branch_policy.denies(Action::Commit) {
+        println!("{reason}");
+        println!("Create a work branch with `wgit start` first.");
         return Ok(());
     }
 
-    if !git::has_staged_changes(cwd)? {
+    if amend {
+        if !git::has_commits(cwd)? {
+            println!("No commits yet. Nothing to amend.");
+            return Ok(());
+        }
+        if !git::has_staged_changes(cwd)? {
+            println!("No staged changes: amend will only reword the last commit.");
+        }
+        if !confirm_rewrite_if_pushed(cwd, &branch_policy, "HEAD", "Amend")? {
+            println!("Amend canceled.");
+            return Ok(());
+        }
+    } else if !git::has_staged_changes(cwd)? {
         println!("No staged changes found. Run `wgit add` first.");
         return Ok(());
     }
 
-    let commit_types = vec![
-        "feat".to_string(),
-        "fix".to_string(),
-        "docs".to_string(),
-        "refactor".to_string(),
-        "test".to_string(),
-        "chore".to_string(),
-    ];
+    let cfg = config::load_config(cwd)?;
+    let issue = match git::recorded_branch_issue(cwd, &current_branch)? {
+        Some(issue) => Some(issue),
+        None => convention::issue_key_from_branch(&cfg.branch_naming, &current_branch),
+    };
+    trailers::ensure_signoff_identity(cwd, &cfg)?;
 
-    let selected = utils::select_one("Select commit type", &commit_types)?;
+    let commit_types = commit_types();
+
+    let mut trailer_rows = trailers::editor_rows(cwd, &cfg)?;
+    let (preselected, initial) = if amend {
+        let existing = git::commit_trailers(cwd, "HEAD")?;
+        let parsed =
+            convention::parse_commit_message(&git::commit_message(cwd, "HEAD")?, &existing);
+        merge_existing_trailers(&mut trailer_rows, existing);
+        // The parsed type is only a starting point; the picker still opens.
+        let preselected = parsed
+            .commit_type
+            .and_then(|value| commit_types.iter().position(|item| *item == value));
+        (preselected.unwrap_or(0), parsed.draft)
+    } else {
+        (0, CommitDraft::default())
+    };
+
+    let selected = utils::select_one_preselected("Select commit type", &commit_types, preselected)?;
     if let Some(index) = selected {
         let commit_type = &commit_types[index];
-        if let Some(draft) = utils::edit_commit_message(commit_type)? {
+        if let Some(mut draft) = utils::edit_commit_message(commit_type, initial, trailer_rows)? {
             if draft.subject.trim().is_empty() {
                 bail!("commit subject cannot be empty");
             }
+            if let Some(issue) = &issue {
+                let already_referenced = draft.scope.contains(issue.as_str())
+                    || draft.body.contains(issue.as_str())
+                    || draft.trailers.iter().any(|(_, value)| value == issue);
+                match cfg.issue.placement {
+                    _ if already_referenced => {}
+                    IssuePlacement::Scope if draft.scope.trim().is_empty() => {
+                        draft.scope = issue.clone();
+                    }
+                    IssuePlacement::Scope | IssuePlacement::Trailer => {
+                        draft
+                            .trailers
+                            .push((cfg.issue.trailer_key.clone(), issue.clone()));
+                    }
+                    IssuePlacement::None => {}
+                }
+            }
 
-            let header = if draft.scope.is_empty() {
-                format!("{commit_type}: {}", draft.subject.trim())
-            } else {
-                format!(
-                    "{commit_type}({}): {}",
-                    draft.scope.trim(),
-                    draft.subject.trim()
-                )
-            };
-            let full_message = if draft.body.trim().is_empty() {
-                header.clone()
-            } else {
-                format!("{header}\n\n{}", draft.body.trim())
-            };
+            let full_message = render_message(cwd, commit_type, &draft)?;
 
             let mut tmp_path = std::env::temp_dir();
             let nanos = SystemTime::now()
@@ -66,7 +105,11 @@ pub fn run() -> Result<()> {
                 )
             })?;
             let tmp_arg = tmp_path.to_string_lossy().to_string();
-            let commit_result = git::run_git_in_dir(&["commit", "-F", &tmp_arg], cwd);
+            let commit_result = if amend {
+                git::commit_amend_from_file(cwd, &tmp_arg)
+            } else {
+                git::run_git_in_dir(&["commit", "-F", &tmp_arg], cwd).map(|_| ())
+            };
             let _ = fs::remove_file(&tmp_path);
             commit_result?;
 
@@ -84,7 +127,14 @@ pub fn run() -> Result<()> {
             if !draft.body.is_empty() {
                 println!("Body:\n{}", draft.body);
             }
-            println!("Commit completed.");
+            for (key, value) in &draft.trailers {
+                println!("Trailer: {key}: {value}");
+            }
+            if amend {
+                println!("Last commit amended.");
+            } else {
+                println!("Commit completed.");
+            }
         } else {
             println!("Commit editor canceled.");
         }
@@ -93,3 +143,83 @@ pub fn run() -> Result<()> {
     }
     Ok(())
 }
+
+pub fn commit_types() -> Vec<String> {
+    vec![
+        "feat".to_string(),
+        "fix".to_string(),
+        "docs".to_string(),
+        "refactor".to_string(),
+        "test".to_string(),
+        "chore".to_string(),
+    ]
+}
+
+/// Builds the final message: conventional header, body, then trailers.
+pub fn render_message(cwd: &Path, commit_type: &str, draft: &CommitDraft) -> Result<String> {
+    let header = if draft.scope.trim().is_empty() {
+        format!("{commit_type}: {}", draft.subject.trim())
+    } else {
+        format!(
+            "{commit_type}({}): {}",
+            draft.scope.trim(),
+            draft.subject.trim()
+        )
+    };
+    let message = if draft.body.trim().is_empty() {
+        header
+    } else {
+        format!("{header}\n\n{}", draft.body.trim())
+    };
+    trailers::apply(cwd, &message, &draft.trailers)
+}
+
+/// Rewriting `commit` is harmless until it is pushed; after that the branch
+/// needs a force push, so warn, respect the force-push rule and confirm.
+pub fn confirm_rewrite_if_pushed(
+    cwd: &Path,
+    branch_policy: &policy::BranchPolicy,
+    commit: &str,
+    action: &str,
+) -> Result<bool> {
+    let remotes = git::remote_branches_containing(cwd, commit)?;
+    if remotes.is_empty() {
+        return Ok(true);
+    }
+    println!(
+        "[Safety Check] This commit is already on {}. {action} rewrites it, so the branch will need `git push --force-with-lease`.",
+        remotes.join(", ")
+    );
+    if let Some(reason) = branch_policy.denies(Action::ForcePush) {
+        println!("{reason}");
+        return Ok(false);
+    }
+    utils::confirm(&format!("{action} a commit that was already pushed?"))
+}
+
+/// Carries the amended commit's trailers into the editor: matching toggles are
+/// switched on, configured keys get their value, anything else becomes a new row.
+pub fn merge_existing_trailers(rows: &mut Vec<TrailerRow>, existing: Vec<(String, String)>) {
+    for (key, value) in existing {
+        let matching = rows.iter_mut().find(|row| {
+            row.key == key
+                && match row.kind {
+                    TrailerKind::Toggle { .. } => row.value == value,
+                    TrailerKind::Text => row.value.is_empty() || row.value == value,
+                }
+        });
+        match matching {
+            Some(row) => {
+                if let TrailerKind::Toggle { enabled, .. } = &mut row.kind {
+                    *enabled = true;
+                }
+                row.value = value;
+            }
+            None => rows.push(TrailerRow {
+                key,
+                value,
+                kind: TrailerKind::Text,
+            }),
+        }
+    }
+}
@@ -0,0 +1,393 @@
+use crate::cli::ConfigAction;
+use crate::config::{self, ConfigEntry, ConfigLayer};
+use crate::settings::{self, Setting, SettingKind};
+use crate::utils;
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub fn run(action: Option<ConfigAction>) -> Result<()> {
+    match action {
+        None => browse(),
+        Some(ConfigAction::Show { origin }) => show(origin),
+        Some(ConfigAction::Get { key }) => get(&key),
+        Some(ConfigAction::Set {
+            key,
+            value,
+            global,
+            team,
+        }) => set(&key, &value, target_layer(global, team)),
+        Some(ConfigAction::Unset { key, global, team }) => unset(&key, target_layer(global, team)),
+    }
+}
+
+fn show(origin: bool) -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Config workflow: merge built-in defaults, global, team and local config (later layers win), then print the effective values."
+    );
+    let layers = config::load_layers(cwd)?;
+    // Values that parse but fail validation would break every other command.
+    layers.resolve()?;
+
+    let files: [(ConfigLayer, Option<PathBuf>); 3] = [
+        (ConfigLayer::Global, config::global_config_path()),
+        (ConfigLayer::Team, Some(config::team_config_path(cwd))),
+        (ConfigLayer::Local, Some(config::config_path(cwd))),
+    ];
+    println!();
+    for (layer, path) in files {
+        let location = match path {
+            Some(path) if path.exists() => path.display().to_string(),
+            Some(path) => format!("{} (not found)", path.display()),
+            None => "no home directory".to_string(),
+        };
+        println!("{:<7} {location}", layer.as_str());
+    }
+    println!();
+
+    let entries = layers.entries();
+    let assignments: Vec<String> = entries
+        .iter()
+        .map(|entry| format!("{} = {}", entry.key, entry.value))
+        .collect();
+    let width = assignments.iter().map(String::len).max().unwrap_or(0);
+    for (entry, assignment) in entries.iter().zip(&assignments) {
+        let lock = if entry.locked { " [locked]" } else { "" };
+        if origin {
+            println!("{assignment:<width$}  # {}{lock}", source(entry));
+        } else {
+            println!("{assignment}{lock}");
+        }
+    }
+    Ok(())
+}
+
+/// Prints only the value so scripts can use it; strings are unquoted.
+fn get(key: &str) -> Result<()> {
+    let parts = config::split_key(key)?;
+    let layers = config::load_layers(Path::new("."))?;
+    let entries: Vec<ConfigEntry> = layers
+        .entries()
+        .into_iter()
+        .filter(|entry| entry.parts.starts_with(&parts))
+        .collect();
+    match entries.as_slice() {
+        [] if settings::find(&parts).is_some() => bail!("`{key}` is not set"),
+        [] => bail!("unknown setting `{key}`. run `wgit config show` to list settings"),
+        [entry] if entry.parts == parts => match entry.value.as_str() {
+            Some(text) => println!("{text}"),
+            None => println!("{}", entry.value),
+        },
+        entries => {
+            for entry in entries {
+                println!("{} = {}", entry.key, entry.value);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn set(key: &str, input: &str, layer: ConfigLayer) -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Config workflow: check the value against the setting's type, write it to the {} config, then reload every layer to confirm.",
+        layer.as_str()
+    );
+    let parts = config::split_key(key)?;
+    let Some(setting) = settings::find(&parts) else {
+        bail!("unknown setting `{key}`. run `wgit config` to browse settings");
+    };
+    if layer == ConfigLayer::Team && config::is_personal(&parts) {
+        bail!("`{key}` cannot be set in the team config; use the local config or --global");
+    }
+    let value = setting.kind.parse(key, input)?;
+    let path = layer_path(cwd, layer)?;
+    println!("{}", apply(cwd, layer, &path, &parts, Some(value))?);
+    Ok(())
+}
+
+fn unset(key: &str, layer: ConfigLayer) -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Config workflow: remove the key from the {} config, then reload every layer to confirm.",
+        layer.as_str()
+    );
+    let parts = config::split_key(key)?;
+    let path = layer_path(cwd, layer)?;
+    println!("{}", apply(cwd, layer, &path, &parts, None)?);
+    Ok(())
+}
+
+/// Lists every known setting; picking one offers set and unset per layer.
+fn browse() -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Config workflow: browse every known setting with its value and source, then set or unset it in the local, team or global config."
+    );
+    let mut status: Option<String> = None;
+    loop {
+        let layers = config::load_layers(cwd)?;
+        layers.resolve()?;
+        let entries = layers.entries();
+        let rows = setting_rows(&entries);
+        let mut labels = Vec::new();
+        let mut previews = Vec::new();
+        for (parts, setting) in &rows {
+            let entry = entries.iter().find(|entry| entry.parts == *parts);
+            let key = config::format_key(parts);
+            labels.push(match entry {
+                Some(entry) => format!("{key} = {}", entry.value),
+                None => format!("{key} (not set)"),
+            });
+            previews.push(preview(&key, setting, entry));
+        }
+
+        let prompt = status
+            .take()
+            .unwrap_or_else(|| "Select a setting to change".to_string());
+        let Some(index) = utils::select_one_with_preview(&prompt, &labels, &previews)? else {
+            println!("Config editor closed.");
+            return Ok(());
+        };
+        let (parts, setting) = &rows[index];
+        let current = entries.iter().find(|entry| entry.parts == *parts);
+        status = match edit(cwd, parts, setting, current) {
+            Ok(message) => message,
+            Err(error) => Some(format!("Error: {error:#}")),
+        };
+    }
+}
+
+/// Known settings in schema order; rule settings repeat for every protected
+/// pattern that is listed or already has rules.
+fn setting_rows(entries: &[ConfigEntry]) -> Vec<(Vec<String>, &'static Setting)> {
+    let mut patterns: Vec<String> = Vec::new();
+    for entry in entries {
+        if entry.parts.first().map(String::as_str) != Some("safety") {
+            continue;
+        }
+        let found: Vec<String> = match entry.parts.get(1).map(String::as_str) {
+            Some("rules") => entry.parts.get(2).cloned().into_iter().collect(),
+            Some("protected_branches") => entry
+                .value
+                .as_array()
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|item| item.as_str().map(str::to_string))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            _ => Vec::new(),
+        };
+        for pattern in found {
+            if !patterns.contains(&pattern) {
+                patterns.push(pattern);
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    for setting in settings::SETTINGS {
+        // `locked` only lives in the team file and is edited with `--team`.
+        if setting.key == config::LOCKED_KEY {
+            continue;
+        }
+        let parts: Vec<String> = setting.key.split('.').map(str::to_string).collect();
+        match parts.iter().position(|part| part == "*") {
+            Some(wildcard) => {
+                for pattern in &patterns {
+                    let mut parts = parts.clone();
+                    parts[wildcard] = pattern.clone();
+                    rows.push((parts, setting));
+                }
+            }
+            None => rows.push((parts, setting)),
+        }
+    }
+    rows
+}
+
+fn preview(key: &str, setting: &Setting, entry: Option<&ConfigEntry>) -> String {
+    let mut lines = vec![
+        key.to_string(),
+        String::new(),
+        setting.description.to_string(),
+        String::new(),
+        format!("Type: {}", setting.kind.describe()),
+    ];
+    match entry {
+        Some(entry) => {
+            lines.push(format!("Value: {}", entry.value));
+            lines.push(format!("Source: {}", source(entry)));
+            if entry.locked {
+                lines.push(String::new());
+                lines.push(
+                    "Locked by the team config: global values are ignored and local values must match."
+                        .to_string(),
+                );
+            }
+        }
+        None => lines.push("Value: not set".to_string()),
+    }
+    lines.join("\n")
+}
+
+/// Asks where to change the setting and, for a set, the new value.
+/// Returns the status line for the next screen, or `None` when canceled.
+fn edit(
+    cwd: &Path,
+    parts: &[String],
+    setting: &Setting,
+    current: Option<&ConfigEntry>,
+) -> Result<Option<String>> {
+    let key = config::format_key(parts);
+    let mut actions: Vec<(String, ConfigLayer, PathBuf, bool)> = Vec::new();
+    for layer in [ConfigLayer::Local, ConfigLayer::Team, ConfigLayer::Global] {
+        if layer == ConfigLayer::Team && config::is_personal(parts) {
+            continue;
+        }
+        let Ok(path) = layer_path(cwd, layer) else {
+            continue;
+        };
+        actions.push((
+            format!("Set in {} config ({})", layer.as_str(), path.display()),
+            layer,
+            path.clone(),
+            true,
+        ));
+        if settings::file_sets(&path, parts) {
+            actions.push((
+                format!("Unset from {} config ({})", layer.as_str(), path.display()),
+                layer,
+                path,
+                false,
+            ));
+        }
+    }
+    let mut labels: Vec<String> = actions.iter().map(|(label, ..)| label.clone()).collect();
+    labels.push("Back".to_string());
+    let Some(choice) = utils::select_one(&format!("Change `{key}`"), &labels)? else {
+        return Ok(None);
+    };
+    let Some((_, layer, path, is_set)) = actions.get(choice) else {
+        return Ok(None);
+    };
+    if !is_set {
+        return apply(cwd, *layer, path, parts, None).map(Some);
+    }
+
+    let prompt = format!("New value for `{key}` ({})", setting.kind.describe());
+    let input = match setting.kind {
+        SettingKind::Bool => pick(&prompt, &["true", "false"])?,
+        SettingKind::Choice(choices) => pick(&prompt, choices)?,
+        _ => {
+            let current = current.map_or_else(|| "not set".to_string(), |e| e.value.to_string());
+            let input = utils::input_text(&format!("{prompt}; current: {current}"))?;
+            (!input.trim().is_empty()).then_some(input)
+        }
+    };
+    let Some(input) = input else {
+        return Ok(None);
+    };
+    let value = setting.kind.parse(&key, &input)?;
+    apply(cwd, *layer, path, parts, Some(value)).map(Some)
+}
+
+fn pick(prompt: &str, choices: &[&str]) -> Result<Option<String>> {
+    let options: Vec<String> = choices.iter().map(ToString::to_string).collect();
+    Ok(utils::select_one(prompt, &options)?.map(|index| options[index].clone()))
+}
+
+/// Writes the change, then reloads every layer; anything that no longer
+/// loads (bad value, locked key) is rolled back.
+fn apply(
+    cwd: &Path,
+    layer: ConfigLayer,
+    path: &Path,
+    parts: &[String],
+    value: Option<toml_edit::Value>,
+) -> Result<String> {
+    let key = config::format_key(parts);
+    let before = fs::read_to_string(path).ok();
+    let is_set = value.is_some();
+    if !settings::write_value(path, parts, value)? {
+        return Ok(format!(
+            "`{key}` is not set in {}; nothing to unset.",
+            path.display()
+        ));
+    }
+    let reloaded = config::load_layers(cwd).and_then(|layers| {
+        layers.resolve()?;
+        Ok(layers)
+    });
+    let layers = match reloaded {
+        Ok(layers) => layers,
+        Err(error) => {
+            let restored = match &before {
+                Some(content) => fs::write(path, content),
+                None => fs::remove_file(path),
+            };
+            restored.with_context(|| {
+                format!("failed to roll back {} after: {error:#}", path.display())
+            })?;
+            return Err(error.context(format!("{} was left unchanged", path.display())));
+        }
+    };
+
+    let action = if is_set { "Set" } else { "Removed" };
+    let mut message = format!("{action} `{key}` in {}.", path.display());
+    match layers
+        .entries()
+        .into_iter()
+        .find(|entry| entry.parts == parts)
+    {
+        Some(entry) if !is_set => message.push_str(&format!(
+            " Effective value is now {} from {}.",
+            entry.value,
+            source(&entry)
+        )),
+        Some(entry) if entry.origin.layer != layer => message.push_str(&format!(
+            " Effective value is still {} from {}.",
+            entry.value,
+            source(&entry)
+        )),
+        None if !is_set => message.push_str(" It is no longer set."),
+        _ => {}
+    }
+    Ok(message)
+}
+
+fn target_layer(global: bool, team: bool) -> ConfigLayer {
+    if global {
+        ConfigLayer::Global
+    } else if team {
+        ConfigLayer::Team
+    } else {
+        ConfigLayer::Local
+    }
+}
+
+fn layer_path(cwd: &Path, layer: ConfigLayer) -> Result<PathBuf> {
+    match layer {
+        ConfigLayer::Global => config::global_config_path()
+            .context("cannot locate the global config; set HOME or XDG_CONFIG_HOME"),
+        ConfigLayer::Team => Ok(config::team_config_path(cwd)),
+        ConfigLayer::Default | ConfigLayer::Local => {
+            if !cwd.join(".git").is_dir() {
+                bail!(
+                    "local config lives in .git/wgit.toml; run this from the repository root or use --global"
+                );
+            }
+            Ok(config::config_path(cwd))
+        }
+    }
+}
+
+fn source(entry: &ConfigEntry) -> String {
+    match &entry.origin.path {
+        Some(path) => format!("{} ({})", entry.origin.layer.as_str(), path.display()),
+        None => entry.origin.layer.as_str().to_string(),
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{config, git, utils};
+use crate::policy::{self, Action};
+use crate::{config, flow, git, utils};
 use anyhow::{Result, bail};
 use std::path::Path;
 
@@ -16,11 +17,11 @@ pub fn run() -> Result<()> {
         return Ok(());
     }
 
+    let cfg = config::load_config(cwd)?;
     let labels: Vec<String> = branches
         .iter()
         .map(|branch| {
-            let protected = config::is_protected_branch(cwd, branch).unwrap_or(false);
-            if protected {
+            if policy::evaluate_with(&cfg, branch).is_protected() {
                 format!("{branch} (protected)")
             } else {
                 branch.clone()
@@ -35,8 +36,10 @@ pub fn run() -> Result<()> {
     };
     let target = &branches[index];
 
-    if config::is_protected_branch(cwd, target)? {
-        bail!("deleting protected branch is blocked: {target}");
+    if let Some(reason) = policy::evaluate_with(&cfg, target).denies(Action::Delete)
+        && !flow::is_finished(cwd, &cfg, target)?
+    {
+        bail!("{reason}");
     }
 
     let deleted_local = if git::try_delete_branch(cwd, target, false)? {
@@ -1,16 +1,32 @@
-use crate::{git, utils};
+use crate::config::{self, MergeStrategy, WgitConfig};
+use crate::flow::{self, BranchKind};
+use crate::policy::{self, Action};
+use crate::utils::{CommitDraft, ConflictChoice};
+use crate::{git, release, trailers, utils};
 use anyhow::{Result, bail};
 use std::path::Path;
 
 pub fn run() -> Result<()> {
     let cwd = Path::new(".");
     println!(
-        "Finish workflow: detect parent branch, squash-merge current branch, commit, then clean up."
+        "Finish workflow: detect parent branch, merge current branch by policy, commit, then clean up."
     );
     let source_branch = git::current_branch(cwd)?;
+    let cfg = config::load_config(cwd)?;
 
-    if source_branch == "main" || source_branch == "master" {
-        println!("Finish is not allowed on protected branch `{source_branch}`.");
+    // Release and hotfix branches from `wgit start` are meant to be finished,
+    // even when a pattern such as `release/*` protects them from direct commits.
+    let kind = flow::branch_kind(&source_branch);
+    if let Some(reason) = policy::evaluate_with(&cfg, &source_branch).denies(Action::FinishFrom)
+        && !flow::is_started_flow_branch(cwd, &source_branch)?
+    {
+        println!("{reason}");
+        if let Some(kind) = kind {
+            println!(
+                "Only {} branches created by `wgit start` can be finished from a protected pattern.",
+                kind.as_str()
+            );
+        }
         return Ok(());
     }
 
@@ -19,12 +35,90 @@ pub fn run() -> Result<()> {
         return Ok(());
     }
 
-    let parent = git::detect_parent_branch(cwd, &source_branch)?;
+    // Release and hotfix branches always finish into the primary branch,
+    // whatever parent was recorded when they were created.
+    let parent = match kind {
+        Some(_) => flow::primary_branch(cwd, &cfg)?,
+        None => git::detect_parent_branch(cwd, &source_branch)?,
+    };
     println!("Current branch: {source_branch}");
-    println!("Detected parent branch: {parent}");
+    match kind {
+        Some(kind) => println!(
+            "Flow `{}` {} branch: finishing into `{parent}`",
+            cfg.flow.model.as_str(),
+            kind.as_str()
+        ),
+        None => println!("Detected parent branch: {parent}"),
+    }
+    let back_merge = match kind {
+        Some(kind) => {
+            let candidates = flow::back_merge_candidates(cwd, &cfg, kind, &source_branch)?;
+            match select_back_merge(kind, candidates)? {
+                Some(target) => Some(target),
+                None if cfg.flow.model == config::FlowModel::Gitflow => {
+                    println!(
+                        "No `{}` or release branch found. Skipping the git-flow back-merge.",
+                        cfg.flow.develop_branch
+                    );
+                    None
+                }
+                None => None,
+            }
+        }
+        None => None,
+    };
+    if let Some(target) = &back_merge {
+        println!("Back-merge branch: {target}");
+    }
 
+    let parent_policy = policy::evaluate_with(&cfg, &parent);
+    // Release and hotfix branches follow the parent's policy too: the
+    // back-merge takes the tagged primary tip, so the tag stays reachable
+    // from the integration branch whichever strategy lands the release.
+    let strategy = match parent_policy.rules.merge_strategies.as_slice() {
+        [only] => *only,
+        strategies => {
+            let labels: Vec<String> = strategies
+                .iter()
+                .map(|strategy| match strategy {
+                    MergeStrategy::Squash => "squash - combine all commits into one".to_string(),
+                    MergeStrategy::Merge => "merge  - keep commits with a merge commit".to_string(),
+                })
+                .collect();
+            let Some(index) =
+                utils::select_one(&format!("Merge strategy allowed for `{parent}`"), &labels)?
+            else {
+                println!("Finish canceled.");
+                return Ok(());
+            };
+            strategies[index]
+        }
+    };
+    println!("Merge strategy: {}", strategy.as_str());
+    let require_tag = parent_policy.rules.require_tag_on_finish || kind.is_some();
+    if let Some(kind) = kind {
+        println!(
+            "{} branches require a release tag after finish.",
+            kind.as_str()
+        );
+    } else if require_tag {
+        println!("`{parent}` requires a release tag after finish.");
+    }
+    if require_tag {
+        // Catch unreadable version files before anything is merged.
+        release::plan_version_bump(cwd, &cfg.release.version_files, "0.0.0")?;
+    }
+
+    let action = match strategy {
+        MergeStrategy::Squash => "Squash-merge",
+        MergeStrategy::Merge => "Merge",
+    };
+    let also = match &back_merge {
+        Some(target) => format!(", merge it back into `{target}`,"),
+        None => String::new(),
+    };
     let confirmed = utils::confirm(&format!(
-        "Squash-merge `{source_branch}` into `{parent}` and delete `{source_branch}`?"
+        "{action} `{source_branch}` into `{parent}`{also} and delete `{source_branch}`?"
     ))?;
     if !confirmed {
         println!("Finish canceled.");
@@ -33,14 +127,14 @@ pub fn run() -> Result<()> {
 
     git::checkout_branch(cwd, &parent)?;
 
-    if let Err(error) = git::squash_merge_branch(cwd, &source_branch) {
-        println!("Squash merge encountered conflicts.");
-        let choice = utils::select_one(
-            "Conflict option",
-            &["abort".to_string(), "continue".to_string()],
-        )?;
-        match choice {
-            Some(0) => {
+    let merge_result = match strategy {
+        MergeStrategy::Squash => git::squash_merge_branch(cwd, &source_branch),
+        MergeStrategy::Merge => git::merge_no_commit(cwd, &source_branch),
+    };
+    if let Err(error) = merge_result {
+        println!("{action} encountered conflicts.");
+        match utils::select_conflict_option("Conflict option")? {
+            Some(ConflictChoice::Abort) => {
                 let _ = git::merge_abort(cwd);
                 let should_reset = utils::confirm(
                     "[Safety Check] Also run `git reset --hard HEAD` to discard conflicted working tree changes?",
@@ -52,14 +146,14 @@ pub fn run() -> Result<()> {
                 println!("Merge aborted and branch restored.");
                 return Ok(());
             }
-            Some(1) => {
+            Some(ConflictChoice::Continue) => {
                 println!("Resolve conflicts manually, then run:");
                 println!("  wgit solve");
                 println!("(solve will check for remaining markers, stage all, and prompt for commit message)");
                 println!("Original git error: {error:#}");
                 return Ok(());
             }
-            _ => {
+            None => {
                 println!("No conflict option selected. Keeping current merge state.");
                 println!("Original git error: {error:#}");
                 return Ok(());
@@ -67,11 +161,19 @@ pub fn run() -> Result<()> {
         }
     }
 
-    let draft = utils::edit_commit_message("merge")?;
+    trailers::ensure_signoff_identity(cwd, &cfg)?;
+    let draft = utils::edit_commit_message(
+        "merge",
+        CommitDraft {
+            body: squashed_summary(cwd, strategy, &source_branch)?,
+            ..CommitDraft::default()
+        },
+        trailers::editor_rows(cwd, &cfg)?,
+    )?;
     let Some(draft) = draft else {
         println!("Merge commit editor canceled.");
         let should_reset = utils::confirm(
-            "[Safety Check] Discard staged squash changes with `git reset --hard HEAD`?",
+            "[Safety Check] Discard staged merge changes with `git reset --hard HEAD`?",
         )?;
         if should_reset {
             let _ = git::reset_hard_head(cwd);
@@ -94,9 +196,10 @@ pub fn run() -> Result<()> {
     } else {
         format!("{msg}\n\n{}", draft.body.trim())
     };
+    let full_msg = trailers::apply(cwd, &full_msg, &draft.trailers)?;
     git::commit_with_message(cwd, &full_msg)?;
 
-    if parent == "main" || parent == "master" {
+    if require_tag {
         let last_tag = git::latest_tag(cwd)?;
         if let Some(tag) = &last_tag {
             println!("Latest tag: {tag}");
@@ -106,7 +209,9 @@ pub fn run() -> Result<()> {
         let new_tag = utils::input_text("New release tag (example: v1.2.3)")?;
         let new_tag = new_tag.trim();
         if new_tag.is_empty() {
-            bail!("tag is required when finishing into protected main branch");
+            bail!(
+                "tag is required when finishing `{source_branch}` into `{parent}` (release/hotfix branch or safety.rules require_tag_on_finish)"
+            );
         }
         if !is_valid_tag(new_tag) {
             bail!("invalid tag format: {new_tag}. expected vMAJOR.MINOR.PATCH");
@@ -121,18 +226,125 @@ pub fn run() -> Result<()> {
                 bail!("new tag must be higher than latest tag `{prev}`");
             }
         }
+        if !cfg.release.version_files.is_empty() {
+            commit_version_bump(cwd, &cfg, new_tag)?;
+        }
         git::create_tag(cwd, new_tag)?;
         println!("Tag `{new_tag}` created.");
     }
 
+    if let Some(target) = &back_merge
+        && !merge_back(cwd, &cfg, &source_branch, &parent, target)?
+    {
+        return Ok(());
+    }
+
     let deleted_local = delete_source_branch(cwd, &source_branch)?;
     if deleted_local {
         maybe_delete_remote_branch(cwd, &source_branch)?;
     }
-    println!("Finished `{source_branch}` into `{parent}`.");
+    match &back_merge {
+        Some(target) => println!(
+            "Finished `{source_branch}` into `{parent}` and merged it back into `{target}`."
+        ),
+        None => println!("Finished `{source_branch}` into `{parent}`."),
+    }
+    Ok(())
+}
+
+/// Body prefilled for a squash merge: the squashed subjects, one per line,
+/// so release notes can still group the work by its conventional type.
+fn squashed_summary(cwd: &Path, strategy: MergeStrategy, source: &str) -> Result<String> {
+    if strategy != MergeStrategy::Squash {
+        return Ok(String::new());
+    }
+    let commits = git::list_commit_entries(cwd, &["--no-merges", &format!("HEAD..{source}")])?;
+    if commits.is_empty() {
+        return Ok(String::new());
+    }
+    let lines: Vec<String> = commits
+        .iter()
+        .map(|commit| format!("- {}", commit.subject))
+        .collect();
+    Ok(format!("Squashed commits:\n{}", lines.join("\n")))
+}
+
+/// Previews the `release.version_files` edits, then commits them as
+/// `chore(release): <tag>` so the tag points at the bumped versions.
+fn commit_version_bump(cwd: &Path, cfg: &WgitConfig, tag: &str) -> Result<()> {
+    let version = tag.trim_start_matches('v');
+    let bump = release::plan_version_bump(cwd, &cfg.release.version_files, version)?;
+    println!("Version file changes for `{tag}`:");
+    for edit in &bump.edits {
+        println!("  {}:{}", edit.path, edit.line);
+        println!("    - {}", edit.before.trim());
+        println!("    + {}", edit.after.trim());
+    }
+    if bump.edits.iter().all(|edit| edit.before == edit.after) {
+        println!("Version files already at {version}. Tagging the merge commit.");
+        return Ok(());
+    }
+    if !utils::confirm(&format!(
+        "Write these changes and commit them as `chore(release): {tag}`?"
+    ))? {
+        println!("Version files left unchanged. Tagging the merge commit.");
+        return Ok(());
+    }
+    release::apply_version_bump(&bump)?;
+    git::stage_paths(cwd, &bump.paths())?;
+    let message = format!("chore(release): {tag}");
+    let message = trailers::apply(cwd, &message, &trailers::enforced(cwd, cfg)?)?;
+    git::commit_with_message(cwd, &message)?;
+    println!("Committed version bump to {version}.");
     Ok(())
 }
 
+/// A hotfix may have several active release branches; ask which one.
+fn select_back_merge(kind: BranchKind, mut candidates: Vec<String>) -> Result<Option<String>> {
+    if candidates.len() <= 1 {
+        return Ok(candidates.pop());
+    }
+    let selected = utils::select_one(
+        &format!("Select branch to merge the {} back into", kind.as_str()),
+        &candidates,
+    )?;
+    match selected {
+        Some(index) => Ok(Some(candidates.swap_remove(index))),
+        None => bail!("a back-merge branch is required under flow.model = \"gitflow\""),
+    }
+}
+
+/// Merges the finished `primary` tip into `target` with a merge commit, so
+/// the integration branch gets the release or hotfix changes together with
+/// the version bump and the tag. Returns `false` on conflicts, leaving the
+/// merge in progress for `wgit solve`.
+fn merge_back(
+    cwd: &Path,
+    cfg: &WgitConfig,
+    source: &str,
+    primary: &str,
+    target: &str,
+) -> Result<bool> {
+    git::checkout_branch(cwd, target)?;
+    if git::is_ancestor(cwd, primary, target)? {
+        println!("`{target}` already contains `{primary}`. Skipping back-merge commit.");
+        return Ok(true);
+    }
+    if let Err(error) = git::merge_no_commit(cwd, primary) {
+        println!("Back-merge of `{source}` into `{target}` encountered conflicts.");
+        println!("Resolve conflicts manually, then run:");
+        println!("  wgit solve");
+        println!("Then remove the finished branch with `wgit delete`.");
+        println!("Original git error: {error:#}");
+        return Ok(false);
+    }
+    let message = format!("merge: back-merge {source} into {target}");
+    let message = trailers::apply(cwd, &message, &trailers::enforced(cwd, cfg)?)?;
+    git::commit_with_message(cwd, &message)?;
+    println!("Merged `{source}` back into `{target}`.");
+    Ok(true)
+}
+
 fn delete_source_branch(cwd: &Path, source_branch: &str) -> Result<bool> {
     if git::try_delete_branch(cwd, source_branch, false)? {
         return Ok(true);
@@ -0,0 +1,88 @@
+use crate::commands::commit::confirm_rewrite_if_pushed;
+use crate::policy::{self, Action};
+use crate::utils::ConflictChoice;
+use crate::{git, utils};
+use anyhow::Result;
+use std::path::Path;
+
+const FALLBACK_COMMIT_LIMIT: usize = 20;
+
+pub fn run() -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Fixup workflow: pick the branch commit to fix, record staged changes as `fixup!`, then autosquash."
+    );
+    let branch = git::current_branch(cwd)?;
+    let branch_policy = policy::evaluate(cwd, &branch)?;
+    if let Some(reason) = branch_policy.denies(Action::Commit) {
+        println!("{reason}");
+        println!("Create a work branch with `wgit start` first.");
+        return Ok(());
+    }
+    if !git::has_staged_changes(cwd)? {
+        println!("No staged changes found. Stage the fix with `wgit add` first.");
+        return Ok(());
+    }
+
+    // Only commits unique to this branch are safe targets; on a branch
+    // without a known parent fall back to recent history.
+    let parent = git::detect_parent_branch(cwd, &branch)?;
+    let commits = if parent != branch {
+        let base = git::branch_upstream(cwd, &parent)?.unwrap_or_else(|| parent.clone());
+        let range = format!("{base}..HEAD");
+        git::list_commits(cwd, &[&range])?
+    } else {
+        git::list_recent_commits(cwd, FALLBACK_COMMIT_LIMIT)?
+    };
+    if commits.is_empty() {
+        println!("`{branch}` has no commits of its own. Use `wgit commit` instead.");
+        return Ok(());
+    }
+
+    let Some(index) = utils::select_one("Select commit to fix", &commits)? else {
+        println!("Fixup canceled.");
+        return Ok(());
+    };
+    let Some(target) = commits[index].split_whitespace().next().map(str::to_string) else {
+        println!("Could not read the selected commit. Fixup canceled.");
+        return Ok(());
+    };
+
+    if !confirm_rewrite_if_pushed(cwd, &branch_policy, &target, "Fixup")? {
+        println!("Fixup canceled.");
+        return Ok(());
+    }
+
+    git::commit_fixup(cwd, &target)?;
+    println!("Recorded fixup for {}.", commits[index]);
+
+    if !utils::confirm("Autosquash now to fold the fixup into its target commit?")? {
+        println!(
+            "Fixup commit kept. Run `wgit fixup` again or `git rebase -i --autosquash` later."
+        );
+        return Ok(());
+    }
+
+    let base = git::parent_commit(cwd, &target)?;
+    if git::autosquash(cwd, base.as_deref())? {
+        println!("Fixup folded into {target}.");
+        return Ok(());
+    }
+
+    println!("Autosquash stopped on conflicts.");
+    match utils::select_conflict_option("Conflict option")? {
+        Some(ConflictChoice::Abort) => {
+            let _ = git::rebase_abort(cwd);
+            println!("Autosquash aborted. The fixup commit is still on `{branch}`.");
+        }
+        Some(ConflictChoice::Continue) => {
+            println!("Resolve conflicts manually, then run:");
+            println!("  git add <files>");
+            println!("  git rebase --continue");
+        }
+        None => {
+            println!("No conflict option selected. Keeping current rebase state.");
+        }
+    }
+    Ok(())
+}
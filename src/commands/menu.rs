@@ -1,41 +1,77 @@
-use crate::cli::Command;
+use crate::cli::{Command, ReleaseAction};
 use crate::utils;
 use anyhow::Result;
 
 pub fn run() -> Result<Option<Command>> {
-    let labels = vec![
-        "init   - initialize repository and wgit config".to_string(),
-        "add    - stage files with multi-select".to_string(),
-        "commit - create structured commit message".to_string(),
-        "delete - delete local branch safely".to_string(),
-        "start  - create and switch to new branch".to_string(),
-        "finish - squash merge current branch".to_string(),
-        "solve  - after resolving conflicts: stage all and commit".to_string(),
-        "remote - detect and add remote aliases".to_string(),
-        "switch - switch local branch".to_string(),
-        "undo   - rollback by commit or operation".to_string(),
-        "sync   - auto stash + pull/push assistant".to_string(),
-        "update - self-update from GitHub release".to_string(),
-        "exit   - close menu".to_string(),
+    let entries: Vec<(&str, Option<Command>)> = vec![
+        ("init   - initialize repository and wgit config", Some(Command::Init)),
+        (
+            "status - branch, changes and stashes at a glance",
+            Some(Command::Status { short: false }),
+        ),
+        ("add    - stage files with multi-select", Some(Command::Add)),
+        ("commit - create structured commit message", Some(Command::Commit { amend: false })),
+        (
+            "amend  - edit the last commit message and contents",
+            Some(Command::Commit { amend: true }),
+        ),
+        (
+            "fixup  - fix an earlier commit on this branch",
+            Some(Command::Fixup),
+        ),
+        ("delete - delete local branch safely", Some(Command::Delete)),
+        ("prune  - clean up merged and stale branches", Some(Command::Prune)),
+        ("start  - create and switch to new branch", Some(Command::Start)),
+        ("finish - squash merge current branch", Some(Command::Finish)),
+        ("solve  - after resolving conflicts: stage all and commit", Some(Command::Solve)),
+        ("remote - detect and add remote aliases", Some(Command::Remote)),
+        ("switch - switch local branch", Some(Command::Switch)),
+        ("undo   - rollback by commit or operation", Some(Command::Undo)),
+        (
+            "sync   - auto stash + pull/push assistant",
+            Some(Command::Sync {
+                fetch_only: false,
+                all_branches: false,
+            }),
+        ),
+        (
+            "tidy   - reorder, squash and reword branch commits",
+            Some(Command::Tidy),
+        ),
+        (
+            "backport - cherry-pick fixes onto release branches",
+            Some(Command::Backport),
+        ),
+        ("refresh - rebase or merge parent updates", Some(Command::Refresh)),
+        ("stash  - browse and manage stash entries", Some(Command::Stash)),
+        (
+            "release - publish the latest tag as a GitHub release",
+            Some(Command::Release {
+                action: ReleaseAction::Publish {
+                    tag: None,
+                    draft: false,
+                    prerelease: false,
+                },
+            }),
+        ),
+        (
+            "config - browse and edit settings",
+            Some(Command::Config { action: None }),
+        ),
+        (
+            "update - self-update from GitHub release",
+            Some(Command::Update {
+                channel: None,
+                version: None,
+                rollback: false,
+                check: false,
+                background_check: false,
+            }),
+        ),
+        ("exit   - close menu", None),
     ];
 
+    let labels: Vec<String> = entries.iter().map(|(label, _)| label.to_string()).collect();
     let selected = utils::select_one("Select a command", &labels)?;
-    let command = match selected {
-        Some(0) => Some(Command::Init),
-        Some(1) => Some(Command::Add),
-        Some(2) => Some(Command::Commit),
-        Some(3) => Some(Command::Delete),
-        Some(4) => Some(Command::Start),
-        Some(5) => Some(Command::Finish),
-        Some(6) => Some(Command::Solve),
-        Some(7) => Some(Command::Remote),
-        Some(8) => Some(Command::Switch),
-        Some(9) => Some(Command::Undo),
-        Some(10) => Some(Command::Sync),
-        Some(11) => Some(Command::Update),
-        Some(12) | None => None,
-        _ => None,
-    };
-
-    Ok(command)
+    Ok(selected.and_then(|idx| entries[idx].1.clone()))
 }
@@ -1,14 +1,23 @@
 pub mod add;
+pub mod backport;
 pub mod commit;
+pub mod config;
 pub mod delete;
 pub mod finish;
+pub mod fixup;
 pub mod init;
 pub mod menu;
+pub mod prune;
+pub mod refresh;
+pub mod release;
 pub mod solve;
 pub mod remote;
 pub mod start;
+pub mod stash;
+pub mod status;
 pub mod switch;
 pub mod sync;
+pub mod tidy;
 pub mod undo;
 pub mod update;
 
@@ -21,16 +30,46 @@ pub fn dispatch(command: Option<Command>) -> Result<()> {
     match command {
         Command::Init => init::run(),
         Command::Add => add::run(),
-        Command::Commit => commit::run(),
+        Command::Commit { amend } => commit::run(amend),
+        Command::Fixup => fixup::run(),
         Command::Delete => delete::run(),
+        Command::Prune => prune::run(),
         Command::Start => start::run(),
         Command::Finish => finish::run(),
         Command::Solve => solve::run(),
         Command::Remote => remote::run(),
         Command::Switch => switch::run(),
         Command::Undo => undo::run(),
-        Command::Sync => sync::run(),
-        Command::Update => update::run(),
+        Command::Sync {
+            fetch_only,
+            all_branches,
+        } => sync::run(fetch_only, all_branches),
+        Command::Tidy => tidy::run(),
+        Command::Backport => backport::run(),
+        Command::Refresh => refresh::run(),
+        Command::Stash => stash::run(),
+        Command::Status { short } => {
+            if let Some(next) = status::run(short)? {
+                dispatch(Some(next))
+            } else {
+                Ok(())
+            }
+        }
+        Command::Release { action } => release::run(action),
+        Command::Config { action } => config::run(action),
+        Command::Update {
+            channel,
+            version,
+            rollback,
+            check,
+            background_check,
+        } => {
+            if background_check {
+                update::refresh_check_cache()
+            } else {
+                update::run(channel, version, rollback, check)
+            }
+        }
         Command::Menu => {
             if let Some(next) = menu::run()? {
                 dispatch(Some(next))
@@ -0,0 +1,228 @@
+use crate::policy::{self, Action};
+use crate::{config, git, utils};
+use anyhow::Result;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum BranchState {
+    Merged,
+    SquashMerged,
+    UpstreamGone,
+    Stale,
+    Active,
+}
+
+impl BranchState {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Merged => "merged",
+            Self::SquashMerged => "squash-merged",
+            Self::UpstreamGone => "upstream gone",
+            Self::Stale => "stale",
+            Self::Active => "active",
+        }
+    }
+
+    /// Merged work already lives in the parent, so deleting it loses nothing.
+    fn is_safe(self) -> bool {
+        matches!(self, Self::Merged | Self::SquashMerged)
+    }
+}
+
+struct Candidate {
+    name: String,
+    parent: String,
+    state: BranchState,
+    last_commit: String,
+    remote_copy: Option<(String, String)>,
+}
+
+pub fn run() -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Prune workflow: classify local branches (merged, squash-merged, upstream gone, stale), then delete the selected ones in one batch."
+    );
+    let cfg = config::load_config(cwd)?;
+    let current = git::current_branch(cwd)?;
+    if !git::list_remotes(cwd)?.is_empty() {
+        git::fetch_all(cwd)?;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |value| value.as_secs() as i64);
+    let stale_cutoff = now - (cfg.stale_branch_days as i64) * 24 * 60 * 60;
+
+    let mut candidates = Vec::new();
+    let mut skipped_protected = Vec::new();
+    for branch in git::list_tracking_branches(cwd)? {
+        if branch.name == current {
+            continue;
+        }
+        if policy::evaluate_with(&cfg, &branch.name)
+            .denies(Action::Delete)
+            .is_some()
+        {
+            skipped_protected.push(branch.name);
+            continue;
+        }
+
+        let parent = git::parent_branch_for(cwd, &branch.name)?;
+        if parent == branch.name {
+            continue;
+        }
+        let base = git::branch_upstream(cwd, &parent)?.unwrap_or_else(|| parent.clone());
+        let state = if is_merged(cwd, &branch.name, &base)? {
+            BranchState::Merged
+        } else if git::is_squash_merged(cwd, &branch.name, &base)? {
+            BranchState::SquashMerged
+        } else if branch.upstream_gone {
+            BranchState::UpstreamGone
+        } else if branch.last_commit_unix > 0 && branch.last_commit_unix < stale_cutoff {
+            BranchState::Stale
+        } else {
+            BranchState::Active
+        };
+
+        let remote_copy = match (&branch.upstream, branch.upstream_gone) {
+            (Some(upstream), false) => upstream
+                .split_once('/')
+                .map(|(remote, name)| (remote.to_string(), name.to_string())),
+            _ => None,
+        };
+        candidates.push(Candidate {
+            name: branch.name,
+            parent: base,
+            state,
+            last_commit: branch.last_commit,
+            remote_copy,
+        });
+    }
+
+    if !skipped_protected.is_empty() {
+        println!("Skipping protected branches: {}", skipped_protected.join(", "));
+    }
+    if candidates.is_empty() {
+        println!("No local branches to clean up.");
+        return Ok(());
+    }
+
+    let labels: Vec<String> = candidates
+        .iter()
+        .map(|candidate| {
+            format!(
+                "{} [{}] vs {} | {}",
+                candidate.name,
+                candidate.state.label(),
+                candidate.parent,
+                candidate.last_commit
+            )
+        })
+        .collect();
+    let preselected: Vec<usize> = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| candidate.state.is_safe())
+        .map(|(idx, _)| idx)
+        .collect();
+    println!(
+        "Stale means no commits in {} days (prune.stale_days).",
+        cfg.stale_branch_days
+    );
+
+    let selected = utils::select_many_preselected(
+        "Select branches to delete (merged ones are pre-selected)",
+        &labels,
+        &preselected,
+    )?;
+    if selected.is_empty() {
+        println!("Prune canceled. No branches selected.");
+        return Ok(());
+    }
+    let targets: Vec<&Candidate> = selected.iter().map(|idx| &candidates[*idx]).collect();
+
+    let remote_count = targets
+        .iter()
+        .filter(|candidate| candidate.remote_copy.is_some())
+        .count();
+    let delete_remote = remote_count > 0
+        && utils::confirm(&format!(
+            "Also delete {remote_count} remote branch copy(ies) of the selected branches?"
+        ))?;
+
+    println!();
+    println!("Prune plan:");
+    for candidate in &targets {
+        let remote_note = match (&candidate.remote_copy, delete_remote) {
+            (Some((remote, name)), true) => format!(" + remote `{remote}/{name}`"),
+            _ => String::new(),
+        };
+        println!(
+            "  delete `{}` [{}]{remote_note}",
+            candidate.name,
+            candidate.state.label()
+        );
+    }
+    println!();
+
+    let unsafe_count = targets
+        .iter()
+        .filter(|candidate| !candidate.state.is_safe())
+        .count();
+    if unsafe_count > 0 {
+        println!(
+            "[Safety Check] {unsafe_count} selected branch(es) are not merged into their parent. Their unmerged commits will be lost."
+        );
+    }
+    let expected = targets.len().to_string();
+    let typed = utils::input_text(&format!(
+        "[Safety Check] Type `{expected}` to delete {expected} branch(es)"
+    ))?;
+    if typed.trim() != expected {
+        println!("Confirmation mismatch. Prune canceled.");
+        return Ok(());
+    }
+
+    let mut deleted = 0usize;
+    let mut failed = Vec::new();
+    for candidate in &targets {
+        // Squash-merged branches always fail `-d`, so the batch uses `-D`
+        // after the explicit confirmation above.
+        if !git::try_delete_branch(cwd, &candidate.name, true)? {
+            failed.push(candidate.name.clone());
+            continue;
+        }
+        deleted += 1;
+        if delete_remote && let Some((remote, name)) = &candidate.remote_copy {
+            if git::remote_branch_exists(cwd, remote, name)? {
+                git::delete_remote_branch(cwd, remote, name)?;
+            } else {
+                println!("Remote branch `{remote}/{name}` no longer exists. Skipped.");
+            }
+        }
+    }
+
+    println!("Deleted {deleted} branch(es).");
+    if !failed.is_empty() {
+        println!("Could not delete: {}", failed.join(", "));
+    }
+    Ok(())
+}
+
+/// A fresh branch that never moved is also an ancestor of its parent, so
+/// it only counts as merged once it has work of its own: a commit beyond
+/// the point it was created at, or a merge commit that brought it in.
+fn is_merged(cwd: &Path, branch: &str, base: &str) -> Result<bool> {
+    if !git::is_ancestor(cwd, branch, base)? {
+        return Ok(false);
+    }
+    if git::is_merged_by_commit(cwd, branch, base)? {
+        return Ok(true);
+    }
+    let Some(created) = git::branch_created_at(cwd, branch)? else {
+        return Ok(false);
+    };
+    let own = git::list_commits(cwd, &[&format!("{created}..{branch}")])?;
+    Ok(!own.is_empty())
+}
@@ -0,0 +1,141 @@
+use crate::config::{self, RefreshStrategy};
+use crate::policy::{self, Action};
+use crate::utils::ConflictChoice;
+use crate::{git, utils};
+use anyhow::Result;
+use std::path::Path;
+
+pub fn run() -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Refresh workflow: fetch remotes, compare with parent branch, then rebase or merge parent updates."
+    );
+    let branch = git::current_branch(cwd)?;
+    let branch_policy = policy::evaluate(cwd, &branch)?;
+    if let Some(reason) = branch_policy.denies(Action::Commit) {
+        println!("{reason}");
+        println!("Use `wgit sync` to update `{branch}` from its remote.");
+        return Ok(());
+    }
+
+    if !git::is_clean_worktree(cwd)? {
+        println!("Please commit or stash your changes before running refresh.");
+        return Ok(());
+    }
+
+    let parent = git::detect_parent_branch(cwd, &branch)?;
+    if !git::list_remotes(cwd)?.is_empty() {
+        git::fetch_all(cwd)?;
+    }
+
+    // Prefer the parent's remote-tracking branch: a local `main` that was never
+    // pulled would make the branch look up to date when it is not.
+    let base = git::branch_upstream(cwd, &parent)?.unwrap_or_else(|| parent.clone());
+    let (ahead, behind) = git::ahead_behind(cwd, &branch, &base)?;
+    println!("Current branch: {branch}");
+    println!("Parent branch: {parent} (comparing with `{base}`)");
+    println!("Ahead of parent: {ahead} commit(s)");
+    println!("Behind parent: {behind} commit(s)");
+
+    if behind == 0 {
+        println!("`{branch}` already contains every commit from `{base}`.");
+        return Ok(());
+    }
+
+    let cfg = config::load_config(cwd)?;
+    let strategy = cfg.refresh_strategy;
+    println!(
+        "Configured strategy: {} (sync.refresh_strategy)",
+        strategy.as_str()
+    );
+
+    let pushed = match git::upstream_remote_and_branch(cwd)? {
+        Some((remote, upstream_branch)) => {
+            git::remote_branch_exists(cwd, &remote, &upstream_branch)?
+        }
+        None => false,
+    };
+    let rewrites_pushed_commits = strategy == RefreshStrategy::Rebase && pushed && ahead > 0;
+    if rewrites_pushed_commits {
+        println!(
+            "[Safety Check] `{branch}` is already pushed. Rebase rewrites its {ahead} local commit(s) and needs a force push afterwards."
+        );
+    }
+
+    let prompt = match strategy {
+        RefreshStrategy::Rebase => format!("Rebase `{branch}` onto `{base}`?"),
+        RefreshStrategy::Merge => format!("Merge `{base}` into `{branch}`?"),
+    };
+    if !utils::confirm(&prompt)? {
+        println!("Refresh canceled.");
+        return Ok(());
+    }
+
+    let result = match strategy {
+        RefreshStrategy::Rebase => git::rebase_onto(cwd, &base),
+        RefreshStrategy::Merge => git::merge_no_edit(cwd, &base),
+    };
+
+    if let Err(error) = result {
+        println!("Updating from parent encountered conflicts.");
+        match utils::select_conflict_option("Conflict option")? {
+            Some(ConflictChoice::Abort) => {
+                match strategy {
+                    RefreshStrategy::Rebase => {
+                        let _ = git::rebase_abort(cwd);
+                    }
+                    RefreshStrategy::Merge => {
+                        let _ = git::merge_abort(cwd);
+                    }
+                }
+                println!("Refresh aborted and branch restored.");
+                return Ok(());
+            }
+            Some(ConflictChoice::Continue) => {
+                println!("Resolve conflicts manually, then run:");
+                match strategy {
+                    RefreshStrategy::Rebase => {
+                        println!("  git add <files>");
+                        println!("  git rebase --continue");
+                        if pushed {
+                            println!("  git push --force-with-lease");
+                        }
+                    }
+                    RefreshStrategy::Merge => {
+                        println!("  wgit solve");
+                    }
+                }
+                println!("Original git error: {error:#}");
+                return Ok(());
+            }
+            None => {
+                println!("No conflict option selected. Keeping current state.");
+                println!("Original git error: {error:#}");
+                return Ok(());
+            }
+        }
+    }
+
+    println!("`{branch}` now includes the latest `{base}` commits.");
+
+    if rewrites_pushed_commits {
+        if let Some(reason) = branch_policy.denies(Action::ForcePush) {
+            println!("{reason}");
+            println!("The rebased branch was not pushed. Consider `merge` as sync.refresh_strategy.");
+            return Ok(());
+        }
+        let confirmed = utils::confirm(
+            "[Safety Check] Push rewritten branch with `git push --force-with-lease`?",
+        )?;
+        if confirmed {
+            git::push_force_with_lease(cwd)?;
+            println!("Remote branch updated.");
+        } else {
+            println!("Skipped push. Run `git push --force-with-lease` when ready.");
+        }
+    } else if pushed {
+        println!("Run `wgit sync` to publish the updated branch.");
+    }
+
+    Ok(())
+}
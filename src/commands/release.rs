@@ -0,0 +1,196 @@
+use crate::cli::ReleaseAction;
+use crate::git::CommitEntry;
+use crate::github::NewRelease;
+use crate::{config, git, github, release, utils};
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+
+/// Hex ed25519 secret key used to sign `SHA256SUMS`.
+const SIGNING_KEY_ENV: &str = "WGIT_SIGNING_KEY";
+
+pub fn run(action: ReleaseAction) -> Result<()> {
+    match action {
+        ReleaseAction::Publish {
+            tag,
+            draft,
+            prerelease,
+        } => publish(tag, draft, prerelease),
+    }
+}
+
+fn publish(tag: Option<String>, draft: bool, prerelease: bool) -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Release publish workflow: push the tag, create a GitHub release with generated notes, then upload assets and SHA256SUMS."
+    );
+    let cfg = config::load_config(cwd)?;
+    let tag = match tag.map(|tag| tag.trim().to_string()) {
+        Some(tag) if !tag.is_empty() => tag,
+        _ => match git::latest_tag(cwd)? {
+            Some(tag) => tag,
+            None => bail!(
+                "no tag found. create one with `wgit finish` or pass it: `wgit release publish v1.2.3`"
+            ),
+        },
+    };
+    if !git::tag_exists(cwd, &tag)? {
+        bail!("tag not found: {tag}");
+    }
+    let Some(repo) = github::origin_repo(cwd)? else {
+        bail!("origin remote is not a GitHub repository; set origin to publish releases");
+    };
+    if !github::has_token() {
+        bail!("GITHUB_TOKEN is not set; it is required to publish releases");
+    }
+
+    let previous = git::latest_tag_on(cwd, &format!("{tag}^"))?;
+    let range = match &previous {
+        Some(previous) => format!("{previous}..{tag}"),
+        None => tag.clone(),
+    };
+    let commits = merged_work(cwd, &range)?;
+    let notes = release::release_notes(&tag, previous.as_deref(), &commits);
+
+    let mut uploads: Vec<(String, Vec<u8>)> = Vec::new();
+    for path in release::resolve_assets(cwd, &cfg.release.assets)? {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        if name == release::CHECKSUMS_NAME
+            || name == release::SIGNATURE_NAME
+            || uploads.iter().any(|(existing, _)| *existing == name)
+        {
+            bail!(
+                "release asset name `{name}` is used twice or clashes with the generated checksum files; rename the file or fix release.assets"
+            );
+        }
+        let bytes = fs::read(&path)
+            .with_context(|| format!("failed to read release asset: {}", path.display()))?;
+        uploads.push((name, bytes));
+    }
+    if !uploads.is_empty() {
+        let sums = release::sha256sums(&uploads);
+        // Builds of `wgit update` with an embedded public key require this signature.
+        let signature = match std::env::var(SIGNING_KEY_ENV) {
+            Ok(secret_key) if !secret_key.trim().is_empty() => Some(
+                release::sign(&secret_key, sums.as_bytes())
+                    .with_context(|| format!("invalid {SIGNING_KEY_ENV}"))?,
+            ),
+            _ => None,
+        };
+        uploads.push((release::CHECKSUMS_NAME.to_string(), sums.into_bytes()));
+        if let Some(signature) = signature {
+            uploads.push((
+                release::SIGNATURE_NAME.to_string(),
+                format!("{signature}\n").into_bytes(),
+            ));
+        }
+    }
+
+    println!();
+    println!("Release `{tag}` for {repo}");
+    match &previous {
+        Some(previous) => println!("Changes since `{previous}`: {} commit(s)", commits.len()),
+        None => println!("First release: {} commit(s)", commits.len()),
+    }
+    if draft || prerelease {
+        let flags: Vec<&str> = [(draft, "draft"), (prerelease, "pre-release")]
+            .into_iter()
+            .filter_map(|(enabled, label)| enabled.then_some(label))
+            .collect();
+        println!("Flags: {}", flags.join(", "));
+    }
+    println!();
+    println!("{notes}");
+    if uploads.is_empty() {
+        println!("Assets: none (configure release.assets to upload build artifacts)");
+    } else {
+        println!("Assets:");
+        for (name, bytes) in &uploads {
+            println!("  {name} ({} bytes)", bytes.len());
+        }
+    }
+    println!();
+    if !utils::confirm(&format!(
+        "Push `{tag}` to `origin` and publish this release on GitHub?"
+    ))? {
+        println!("Release publish canceled.");
+        return Ok(());
+    }
+
+    git::push_tag(cwd, "origin", &tag)?;
+    let api_base = &cfg.github_api_base;
+    let created = github::create_release(
+        api_base,
+        &repo,
+        &NewRelease {
+            tag_name: &tag,
+            name: &tag,
+            body: &notes,
+            draft,
+            prerelease,
+        },
+    )?;
+    println!("Created release: {}", created.html_url);
+
+    let total = uploads.len();
+    for (index, (name, bytes)) in uploads.into_iter().enumerate() {
+        println!("Uploading {name} ({}/{total})", index + 1);
+        github::upload_release_asset(api_base, &created, &name, bytes).with_context(|| {
+            format!(
+                "release was created but asset upload stopped; finish it on {}",
+                created.html_url
+            )
+        })?;
+    }
+    println!("Published `{tag}`.");
+    Ok(())
+}
+
+/// Commits for the notes of `range`. `merge:` commits written by `finish`
+/// stand for the work they merged: a merge commit is replaced by the commits
+/// on its merged side, a squash commit by the subjects listed in its body.
+fn merged_work(cwd: &Path, range: &str) -> Result<Vec<CommitEntry>> {
+    let mut work = Vec::new();
+    for commit in git::list_commit_entries(cwd, &["--first-parent", range])? {
+        if !release::is_merge_subject(&commit.subject) {
+            work.push(commit);
+            continue;
+        }
+        if git::merged_parent(cwd, &commit.hash)?.is_none() {
+            work.extend(squashed_work(cwd, commit)?);
+            continue;
+        }
+        let side = format!("{0}^1..{0}", commit.hash);
+        let merged = git::list_commit_entries(cwd, &["--no-merges", &side])?;
+        if merged.is_empty() {
+            work.push(commit);
+        }
+        for entry in merged {
+            if release::is_merge_subject(&entry.subject) {
+                work.extend(squashed_work(cwd, entry)?);
+            } else {
+                work.push(entry);
+            }
+        }
+    }
+    Ok(work)
+}
+
+/// The subjects listed in a squash commit's body, under its hash; the
+/// commit itself when the body lists none.
+fn squashed_work(cwd: &Path, commit: CommitEntry) -> Result<Vec<CommitEntry>> {
+    let subjects = release::squashed_subjects(&git::commit_message(cwd, &commit.hash)?);
+    if subjects.is_empty() {
+        return Ok(vec![commit]);
+    }
+    Ok(subjects
+        .into_iter()
+        .map(|subject| CommitEntry {
+            hash: commit.hash.clone(),
+            subject,
+        })
+        .collect())
+}
@@ -1,4 +1,5 @@
-use crate::{git, utils};
+use crate::utils::CommitDraft;
+use crate::{config, git, trailers, utils};
 use anyhow::{Result, bail};
 use std::path::Path;
 
@@ -26,7 +27,13 @@ pub fn run() -> Result<()> {
         return Ok(());
     }
 
-    let draft = utils::edit_commit_message("merge")?;
+    let cfg = config::load_config(cwd)?;
+    trailers::ensure_signoff_identity(cwd, &cfg)?;
+    let draft = utils::edit_commit_message(
+        "merge",
+        CommitDraft::default(),
+        trailers::editor_rows(cwd, &cfg)?,
+    )?;
     let Some(draft) = draft else {
         println!("Commit editor canceled. Staged changes are unchanged.");
         return Ok(());
@@ -44,6 +51,7 @@ pub fn run() -> Result<()> {
     } else {
         format!("{msg}\n\n{}", draft.body.trim())
     };
+    let full_msg = trailers::apply(cwd, &full_msg, &draft.trailers)?;
     git::commit_with_message(cwd, &full_msg)?;
     println!("Commit completed.");
     Ok(())
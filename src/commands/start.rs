@@ -1,19 +1,15 @@
-use crate::{config, git, utils};
+use crate::config::WgitConfig;
+use crate::convention::IssueRef;
+use crate::flow::{self, BranchKind};
+use crate::{config, convention, git, github, utils};
 use anyhow::{Result, bail};
 use std::path::Path;
 
-fn is_basic_branch_name_valid(input: &str) -> bool {
-    if input.is_empty() || input.contains(' ') || input.ends_with('/') || input.starts_with('/') {
-        return false;
-    }
-    !input.contains("..") && !input.contains("~") && !input.contains("^") && !input.contains(':')
-}
-
 pub fn run() -> Result<()> {
     let cwd = Path::new(".");
     println!("Start workflow: pick branch type, validate branch name, then create and switch.");
+    let cfg = config::load_config(cwd)?;
     if !git::has_commits(cwd)? {
-        let cfg = config::load_config(cwd)?;
         if cfg.auto_bootstrap_commit_on_start {
             println!("No commit history detected. Auto bootstrap commit is enabled.");
             git::create_empty_commit(cwd, "chore: bootstrap repository baseline")?;
@@ -30,38 +26,145 @@ pub fn run() -> Result<()> {
         }
     }
 
-    let branch_types = vec![
-        "feature".to_string(),
-        "bugfix".to_string(),
-        "hotfix".to_string(),
-        "release".to_string(),
-    ];
+    let naming = &cfg.branch_naming;
+    let branch_types = &naming.types;
 
-    let selected = utils::select_one("Select branch type", &branch_types)?;
+    let selected = utils::select_one("Select branch type", branch_types)?;
     if let Some(index) = selected {
-        let raw_name = utils::input_text("Branch name")?;
-        let name = raw_name.trim();
+        let raw_issue =
+            utils::input_text("Issue reference (optional, e.g. PROJ-123 or #42; Enter to skip)")?;
+        let issue = match raw_issue.trim() {
+            "" => None,
+            value => match convention::parse_issue_ref(naming, value) {
+                Some(issue) => Some(issue),
+                None => bail!(
+                    "invalid issue reference `{value}`: expected `#<number>` or a key matching branch.issue_key_pattern `{}`",
+                    naming.issue_key_pattern.as_str()
+                ),
+            },
+        };
+        let issue_title = match &issue {
+            Some(issue) => lookup_issue_title(cwd, &cfg.github_api_base, issue)?,
+            None => None,
+        };
+
+        let prompt = if issue_title.is_some() {
+            "Branch name (Enter uses the issue title)"
+        } else {
+            "Branch name (example: Login Form)"
+        };
+        let raw_name = utils::input_text(prompt)?;
+        let name = match (raw_name.trim(), &issue_title) {
+            ("", Some(title)) => title.as_str(),
+            (name, _) => name,
+        };
         if name.is_empty() {
             println!("Branch creation canceled: empty name.");
             return Ok(());
         }
-        if !is_basic_branch_name_valid(name) {
-            bail!("invalid branch name: {name}");
+        let mut slug = convention::slugify(name);
+        if slug != name {
+            println!("Branch name normalized: `{name}` -> `{slug}`");
         }
-        let full_branch = format!("{}/{}", branch_types[index], name);
+        if let Some(issue) = &issue {
+            let issue_slug = issue.slug();
+            if slug != issue_slug && !slug.starts_with(&format!("{issue_slug}-")) {
+                slug = format!("{issue_slug}-{slug}");
+            }
+        }
+        let full_branch = convention::build_branch_name(naming, &branch_types[index], &slug)?;
         if !git::is_valid_branch_ref(cwd, &full_branch)? {
-            bail!(
-                "invalid branch name by git rules: {full_branch}. example: feature/login-form"
-            );
+            bail!("invalid branch name by git rules: {full_branch}. example: feature/login-form");
         }
         if git::branch_exists(cwd, &full_branch)? {
             bail!("branch already exists: {full_branch}");
         }
 
-        git::run_git_in_dir(&["checkout", "-b", &full_branch], cwd)?;
+        let (start_point, parent) = match BranchKind::from_type(&branch_types[index]) {
+            Some(kind) => {
+                let Some(start_point) = flow_start_point(cwd, &cfg, kind)? else {
+                    println!("Start command canceled.");
+                    return Ok(());
+                };
+                (Some(start_point), flow::primary_branch(cwd, &cfg)?)
+            }
+            None => (None, git::current_branch(cwd)?),
+        };
+        match &start_point {
+            Some(start_point) => git::create_branch_from(cwd, &full_branch, start_point)?,
+            None => {
+                git::run_git_in_dir(&["checkout", "-b", &full_branch], cwd)?;
+            }
+        }
+        if !parent.is_empty() {
+            git::record_parent_branch(cwd, &full_branch, &parent)?;
+        }
+        if let Some(issue) = &issue {
+            git::record_branch_issue(cwd, &full_branch, &issue.key)?;
+            println!("Linked `{full_branch}` to issue {}.", issue.key);
+        }
         println!("Created and switched to `{full_branch}`.");
     } else {
         println!("Start command canceled.");
     }
     Ok(())
 }
+
+/// Release branches start from the flow's release base. Hotfixes start from
+/// the primary branch, or from its latest tag when unreleased commits sit on top.
+fn flow_start_point(cwd: &Path, cfg: &WgitConfig, kind: BranchKind) -> Result<Option<String>> {
+    let base = match kind {
+        BranchKind::Release => flow::release_base(cwd, cfg)?,
+        BranchKind::Hotfix => flow::primary_branch(cwd, cfg)?,
+    };
+    println!(
+        "Flow `{}`: {} branches start from `{base}` and finish into `{}`.",
+        cfg.flow.model.as_str(),
+        kind.as_str(),
+        flow::primary_branch(cwd, cfg)?
+    );
+    if kind == BranchKind::Release {
+        return Ok(Some(base));
+    }
+    let Some(tag) = git::latest_tag_on(cwd, &base)? else {
+        return Ok(Some(base));
+    };
+    let unreleased = git::list_commits(cwd, &[&format!("{tag}..{base}")])?;
+    if unreleased.is_empty() {
+        return Ok(Some(base));
+    }
+    let options = vec![
+        format!("latest tag `{tag}` - patch exactly what was released"),
+        format!(
+            "`{base}` tip - include {} unreleased commit(s)",
+            unreleased.len()
+        ),
+    ];
+    Ok(utils::select_one("Cut hotfix from", &options)?.map(
+        |choice| {
+            if choice == 0 { tag } else { base }
+        },
+    ))
+}
+
+/// Only `#42` style references on a GitHub origin have a title to look up.
+/// A failed lookup falls back to typing the name by hand.
+fn lookup_issue_title(cwd: &Path, api_base: &str, issue: &IssueRef) -> Result<Option<String>> {
+    let Some(number) = issue.number else {
+        return Ok(None);
+    };
+    let Some(repo) = github::origin_repo(cwd)? else {
+        println!("Origin is not a GitHub repository. Skipping issue title lookup.");
+        return Ok(None);
+    };
+    match github::fetch_issue_title(api_base, &repo, number) {
+        Ok(title) => {
+            println!("Issue #{number}: {title}");
+            Ok(Some(title))
+        }
+        Err(error) => {
+            println!("Could not fetch issue #{number} title: {error:#}");
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,199 @@
+use crate::git::StashEntry;
+use crate::{git, utils};
+use anyhow::{Result, bail};
+use std::path::Path;
+
+const CREATE_LABEL: &str = "+ create new stash from current changes";
+
+pub fn run() -> Result<()> {
+    let cwd = Path::new(".");
+    println!("Stash workflow: list saved stashes with preview, then apply, pop, drop, rename or branch.");
+    let entries = git::list_stashes(cwd)?;
+    let dirty = git::has_uncommitted_changes(cwd)?;
+    if entries.is_empty() && !dirty {
+        println!("No stash entries found and no local changes to stash.");
+        return Ok(());
+    }
+
+    let mut labels = Vec::new();
+    let mut previews = Vec::new();
+    if dirty {
+        labels.push(CREATE_LABEL.to_string());
+        previews.push("Save current uncommitted changes as a new stash entry.".to_string());
+    }
+    for entry in &entries {
+        let patch = git::stash_show_patch(cwd, &entry.stash_ref)?;
+        let files = changed_files(&patch);
+        labels.push(stash_label(entry, files.len()));
+        previews.push(format!(
+            "{} ({})\nBranch: {}\nCreated: {}\nFiles:\n{}\n\n{patch}",
+            entry.stash_ref,
+            &entry.id[..entry.id.len().min(7)],
+            if entry.branch.is_empty() { "(unknown)" } else { &entry.branch },
+            entry.age,
+            files
+                .iter()
+                .map(|file| format!("  {file}"))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ));
+    }
+
+    let selected = utils::select_one_with_preview("Select stash entry", &labels, &previews)?;
+    let Some(index) = selected else {
+        println!("Stash canceled.");
+        return Ok(());
+    };
+
+    let offset = usize::from(dirty);
+    if dirty && index == 0 {
+        return create_stash(cwd);
+    }
+    let entry = &entries[index - offset];
+
+    let actions = vec![
+        "apply  - restore changes and keep the stash".to_string(),
+        "pop    - restore changes and remove the stash".to_string(),
+        "drop   - delete the stash".to_string(),
+        "rename - change the stash message".to_string(),
+        "branch - create a new branch from the stash".to_string(),
+    ];
+    let action = utils::select_one(&format!("Action for {}", entry.stash_ref), &actions)?;
+    match action {
+        Some(0) => apply_stash(cwd, entry, false),
+        Some(1) => apply_stash(cwd, entry, true),
+        Some(2) => drop_stash(cwd, entry),
+        Some(3) => rename_stash(cwd, entry),
+        Some(4) => branch_from_stash(cwd, entry),
+        _ => {
+            println!("Stash canceled.");
+            Ok(())
+        }
+    }
+}
+
+fn stash_label(entry: &StashEntry, file_count: usize) -> String {
+    let branch = if entry.branch.is_empty() {
+        String::new()
+    } else {
+        format!("[{}] ", entry.branch)
+    };
+    format!(
+        "{}  {branch}{}  ({}, {file_count} file(s))",
+        entry.stash_ref, entry.message, entry.age
+    )
+}
+
+fn changed_files(patch: &str) -> Vec<String> {
+    let mut files: Vec<String> = patch
+        .lines()
+        .filter_map(|line| line.strip_prefix("diff --git a/"))
+        .filter_map(|rest| rest.split_once(" b/").map(|(path, _)| path.to_string()))
+        .collect();
+    files.dedup();
+    files
+}
+
+fn create_stash(cwd: &Path) -> Result<()> {
+    let message = utils::input_text("Stash message (example: half-done login form)")?;
+    let message = message.trim();
+    if message.is_empty() {
+        println!("Stash creation canceled: empty message.");
+        return Ok(());
+    }
+    let include_untracked = utils::confirm("Include untracked (new) files?")?;
+    match git::stash_push(cwd, message, include_untracked)? {
+        Some(id) => println!("Saved stash {} \"{message}\".", &id[..id.len().min(7)]),
+        None => println!("No local changes to save."),
+    }
+    Ok(())
+}
+
+fn apply_stash(cwd: &Path, entry: &StashEntry, remove: bool) -> Result<()> {
+    let ok = if remove {
+        git::stash_pop(cwd, &entry.stash_ref)?
+    } else {
+        git::stash_apply(cwd, &entry.stash_ref)?
+    };
+    if ok {
+        if remove {
+            println!("Restored and removed {}.", entry.stash_ref);
+        } else {
+            println!("Restored {}. The stash entry is kept.", entry.stash_ref);
+        }
+        return Ok(());
+    }
+
+    println!("Stash could not be applied cleanly. The stash entry is kept.");
+    let conflicted = git::unmerged_files(cwd)?;
+    if !conflicted.is_empty() {
+        println!("Files with conflicts:");
+        for path in &conflicted {
+            println!("  {path}");
+        }
+        println!("Resolve the conflict markers, then run `git restore --staged .`.");
+    } else {
+        println!("Commit or stash your current changes first, then try again.");
+    }
+    Ok(())
+}
+
+fn drop_stash(cwd: &Path, entry: &StashEntry) -> Result<()> {
+    println!("[Safety Check] Dropping a stash discards the changes saved in it.");
+    if !utils::confirm(&format!("Drop {} \"{}\"?", entry.stash_ref, entry.message))? {
+        println!("Drop canceled.");
+        return Ok(());
+    }
+    let expected = entry.id.chars().take(7).collect::<String>();
+    let typed = utils::input_text(&format!(
+        "[Safety Check] Type `{expected}` to confirm drop"
+    ))?;
+    if typed.trim() != expected {
+        println!("Stash id mismatch. Drop canceled.");
+        return Ok(());
+    }
+    git::stash_drop(cwd, &entry.stash_ref)?;
+    println!("Dropped {} ({expected}).", entry.stash_ref);
+    Ok(())
+}
+
+fn rename_stash(cwd: &Path, entry: &StashEntry) -> Result<()> {
+    let message = utils::input_text(&format!("New message for {}", entry.stash_ref))?;
+    let message = message.trim();
+    if message.is_empty() {
+        println!("Rename canceled: empty message.");
+        return Ok(());
+    }
+
+    // Git cannot rename in place: store the same commit under the new message,
+    // then drop the old entry, which has moved down by one position.
+    git::stash_store(cwd, &entry.id, message)?;
+    let old_ref = git::list_stashes(cwd)?
+        .into_iter()
+        .skip(1)
+        .find(|candidate| candidate.id == entry.id)
+        .map(|candidate| candidate.stash_ref);
+    if let Some(old_ref) = old_ref {
+        git::stash_drop(cwd, &old_ref)?;
+    }
+    println!("Renamed stash to \"{message}\" (now stash@{{0}}).");
+    Ok(())
+}
+
+fn branch_from_stash(cwd: &Path, entry: &StashEntry) -> Result<()> {
+    let name = utils::input_text("New branch name (example: feature/login-form)")?;
+    let name = name.trim();
+    if name.is_empty() {
+        println!("Branch creation canceled: empty name.");
+        return Ok(());
+    }
+    if !git::is_valid_branch_ref(cwd, name)? {
+        bail!("invalid branch name by git rules: {name}. example: feature/login-form");
+    }
+    if git::branch_exists(cwd, name)? {
+        bail!("branch already exists: {name}");
+    }
+    git::stash_branch(cwd, name, &entry.stash_ref)?;
+    println!("Created `{name}` from {} and applied its changes.", entry.stash_ref);
+    Ok(())
+}
@@ -0,0 +1,245 @@
+use crate::cli::Command;
+use crate::config;
+use crate::git;
+use crate::policy::{self, Action};
+use crate::utils::{self, DashboardPanel};
+use anyhow::{Result, bail};
+use std::path::Path;
+
+const SHORTCUTS: [(char, &str); 4] = [
+    ('a', "add"),
+    ('c', "commit"),
+    ('s', "sync"),
+    ('f', "finish"),
+];
+
+/// Everything the dashboard and `--short` show, already worded for display.
+struct Snapshot {
+    branch: String,
+    parent: Option<String>,
+    upstream: String,
+    tag: Option<String>,
+    staged: Vec<String>,
+    unstaged: Vec<String>,
+    untracked: Vec<String>,
+    stashes: Vec<String>,
+    warnings: Vec<String>,
+}
+
+/// Shows the dashboard and returns the command picked with a shortcut.
+pub fn run(short: bool) -> Result<Option<Command>> {
+    let cwd = Path::new(".");
+    if !git::is_git_repo(cwd)? {
+        bail!("not a git repository; run `wgit init` first");
+    }
+    if short {
+        print_short(&git::quietly(|| collect(cwd))?);
+        return Ok(None);
+    }
+
+    println!(
+        "Status workflow: read branch, upstream, changes, stashes and in-progress operations, then open the dashboard."
+    );
+    let snapshot = git::quietly(|| collect(cwd))?;
+    let summary = vec![
+        format!("Branch    {}", snapshot.branch),
+        format!(
+            "Parent    {}",
+            snapshot.parent.as_deref().unwrap_or("none recorded")
+        ),
+        format!("Upstream  {}", snapshot.upstream),
+        format!("Tag       {}", snapshot.tag.as_deref().unwrap_or("none")),
+    ];
+    let columns = [
+        panel("Staged", &snapshot.staged),
+        panel("Unstaged", &snapshot.unstaged),
+        panel("Untracked", &snapshot.untracked),
+    ];
+    let stashes = panel("Stashes", &snapshot.stashes);
+
+    let choice =
+        utils::show_dashboard(&summary, &snapshot.warnings, &columns, &stashes, &SHORTCUTS)?;
+    Ok(match choice {
+        Some('a') => Some(Command::Add),
+        Some('c') => Some(Command::Commit { amend: false }),
+        Some('s') => Some(Command::Sync {
+            fetch_only: false,
+            all_branches: false,
+        }),
+        Some('f') => Some(Command::Finish),
+        _ => None,
+    })
+}
+
+fn panel(title: &str, lines: &[String]) -> DashboardPanel {
+    DashboardPanel {
+        title: format!("{title} ({})", lines.len()),
+        lines: lines.to_vec(),
+    }
+}
+
+/// One `key: value` line per fact so the output can be grepped.
+fn print_short(snapshot: &Snapshot) {
+    println!("branch: {}", snapshot.branch);
+    if let Some(parent) = &snapshot.parent {
+        println!("parent: {parent}");
+    }
+    println!("upstream: {}", snapshot.upstream);
+    if let Some(tag) = &snapshot.tag {
+        println!("tag: {tag}");
+    }
+    let groups = [
+        ("staged", &snapshot.staged),
+        ("unstaged", &snapshot.unstaged),
+        ("untracked", &snapshot.untracked),
+        ("stash", &snapshot.stashes),
+    ];
+    if groups[..3].iter().all(|(_, lines)| lines.is_empty()) {
+        println!("changes: none");
+    }
+    for (label, lines) in groups {
+        for line in lines {
+            println!("{label}: {line}");
+        }
+    }
+    for warning in &snapshot.warnings {
+        println!("warning: {warning}");
+    }
+}
+
+fn collect(cwd: &Path) -> Result<Snapshot> {
+    let cfg = config::load_config(cwd)?;
+    let has_commits = git::has_commits(cwd)?;
+    let name = git::current_branch(cwd)?.trim().to_string();
+    let branch = if name.is_empty() {
+        match (git::rebasing_branch(cwd), git::short_head(cwd)?) {
+            (Some(rebasing), Some(head)) => format!("{rebasing} (rebasing, HEAD at {head})"),
+            (None, Some(head)) => format!("detached HEAD at {head}"),
+            _ => "detached HEAD".to_string(),
+        }
+    } else if has_commits {
+        name.clone()
+    } else {
+        format!("{name} (no commits yet)")
+    };
+
+    let recorded = if name.is_empty() {
+        None
+    } else {
+        git::recorded_parent_branch(cwd, &name)?
+    };
+    let parent = match recorded {
+        Some(parent) if parent == name => None,
+        Some(parent) if has_commits && git::branch_exists(cwd, &parent)? => {
+            let (ahead, behind) = git::ahead_behind(cwd, &name, &parent)?;
+            Some(format!("{parent}, {}", counts(ahead, behind)))
+        }
+        Some(parent) => Some(format!("{parent} (branch not found)")),
+        None => None,
+    };
+
+    let upstream = if name.is_empty() {
+        "none (detached HEAD)".to_string()
+    } else {
+        match git::upstream_branch(cwd)? {
+            Some(upstream) if has_commits => {
+                let (ahead, behind) = git::ahead_behind(cwd, "HEAD", &upstream)?;
+                format!("{upstream}, {}", counts(ahead, behind))
+            }
+            Some(upstream) => upstream,
+            None => "not published; `wgit sync` pushes the branch".to_string(),
+        }
+    };
+
+    let tag = match git::latest_tag(cwd)? {
+        Some(tag) if has_commits => {
+            let since = git::list_commits(cwd, &[&format!("{tag}..HEAD")])?.len();
+            Some(match since {
+                0 => format!("{tag} (at HEAD)"),
+                1 => format!("{tag}, 1 commit since"),
+                _ => format!("{tag}, {since} commits since"),
+            })
+        }
+        _ => None,
+    };
+
+    let mut staged = Vec::new();
+    let mut unstaged = Vec::new();
+    let mut untracked = Vec::new();
+    for entry in git::working_tree_entries(cwd)? {
+        let (index, worktree) = (entry.index_status, entry.worktree_status);
+        if index == '?' {
+            untracked.push(entry.path);
+        } else if index == 'U' || worktree == 'U' || (index == worktree && "AD".contains(index)) {
+            unstaged.push(format!("conflict {}", entry.path));
+        } else {
+            if index != ' ' {
+                staged.push(format!("{index} {}", entry.path));
+            }
+            if worktree != ' ' {
+                unstaged.push(format!("{worktree} {}", entry.path));
+            }
+        }
+    }
+
+    let stashes = git::list_stashes(cwd)?
+        .into_iter()
+        .map(|entry| {
+            let branch = if entry.branch.is_empty() {
+                String::new()
+            } else {
+                format!("on {}, ", entry.branch)
+            };
+            format!(
+                "{} {} ({branch}{})",
+                entry.stash_ref, entry.message, entry.age
+            )
+        })
+        .collect();
+
+    let mut warnings = Vec::new();
+    match git::operation_in_progress(cwd) {
+        Some("merge") => warnings.push(
+            "A merge is in progress: resolve any conflicts, then run `wgit solve`.".to_string(),
+        ),
+        Some(operation) => warnings.push(format!(
+            "A {operation} is in progress: resolve any conflicts, then run `git {operation} --continue` (or `--abort`)."
+        )),
+        None => {}
+    }
+    if !name.is_empty() {
+        let branch_policy = policy::evaluate_with(&cfg, &name);
+        match branch_policy.denies(Action::Commit) {
+            Some(reason) if !staged.is_empty() || !unstaged.is_empty() => warnings.push(format!(
+                "{reason} Run `wgit start` to move these changes to a new branch."
+            )),
+            Some(reason) => {
+                warnings.push(format!("{reason} Run `wgit start` before making changes."))
+            }
+            None if branch_policy.is_protected() => {
+                warnings.push(format!("`{name}` is a protected branch."));
+            }
+            None => {}
+        }
+    }
+
+    Ok(Snapshot {
+        branch,
+        parent,
+        upstream,
+        tag,
+        staged,
+        unstaged,
+        untracked,
+        stashes,
+        warnings,
+    })
+}
+
+fn counts(ahead: usize, behind: usize) -> String {
+    if ahead == 0 && behind == 0 {
+        "up to date".to_string()
+    } else {
+        format!("{ahead} ahead, {behind} behind")
+    }
+}
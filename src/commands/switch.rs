@@ -2,27 +2,63 @@ use crate::{git, utils};
 use anyhow::Result;
 use std::path::Path;
 
+const REFLOG_SCAN_LIMIT: usize = 300;
+const PARK_PREFIX: &str = "wgit park:";
+
+enum SwitchTarget {
+    Local(String),
+    Remote(git::RemoteBranch),
+}
+
 pub fn run() -> Result<()> {
     let cwd = Path::new(".");
-    println!("Switch workflow: list branches, choose target, then checkout selected branch.");
+    println!(
+        "Switch workflow: list local and remote branches by recent use, choose target, then checkout."
+    );
     let current = git::current_branch(cwd)?;
-    let mut branches = git::list_local_branches(cwd)?;
-    if branches.is_empty() {
+    let mut locals = git::list_tracking_branches(cwd)?;
+    if locals.is_empty() {
         println!("No local branches available.");
         return Ok(());
     }
 
-    branches.sort();
-    let labels: Vec<String> = branches
-        .iter()
-        .map(|branch| {
-            if branch == &current {
-                format!("{branch} (current)")
-            } else {
-                branch.clone()
-            }
-        })
+    // Most recently checked-out branches first; never-visited ones follow alphabetically.
+    let recent = git::recently_checked_out_branches(cwd, REFLOG_SCAN_LIMIT)?;
+    let rank = |name: &str| {
+        if name == current {
+            return 0;
+        }
+        recent
+            .iter()
+            .position(|branch| branch == name)
+            .map_or(usize::MAX, |idx| idx + 1)
+    };
+    locals.sort_by(|left, right| {
+        rank(&left.name)
+            .cmp(&rank(&right.name))
+            .then_with(|| left.name.cmp(&right.name))
+    });
+
+    let mut remotes: Vec<git::RemoteBranch> = git::list_remote_branches(cwd)?
+        .into_iter()
+        .filter(|remote| !locals.iter().any(|local| local.name == remote.branch))
         .collect();
+    remotes.sort_by_key(git::RemoteBranch::full_name);
+
+    let mut labels = Vec::new();
+    let mut targets = Vec::new();
+    for branch in &locals {
+        labels.push(local_label(branch, &current));
+        targets.push(SwitchTarget::Local(branch.name.clone()));
+    }
+    for remote in remotes {
+        labels.push(format!(
+            "{} (remote only) | {}",
+            remote.full_name(),
+            remote.last_commit
+        ));
+        targets.push(SwitchTarget::Remote(remote));
+    }
 
     let selected = utils::select_one("Select branch to switch", &labels)?;
     let Some(selected_idx) = selected else {
@@ -30,23 +66,111 @@ pub fn run() -> Result<()> {
         return Ok(());
     };
 
-    let target = &branches[selected_idx];
-    if target == &current {
+    let target_name = match &targets[selected_idx] {
+        SwitchTarget::Local(name) => name.clone(),
+        SwitchTarget::Remote(remote) => remote.branch.clone(),
+    };
+    if target_name == current {
         println!("Already on `{current}`.");
         return Ok(());
     }
 
     if !git::is_clean_worktree(cwd)? {
-        let confirmed = utils::confirm(
-            "Working tree is not clean. Continue switching branch anyway (possible conflicts)?",
-        )?;
-        if !confirmed {
-            println!("Switch canceled due to uncommitted changes.");
-            return Ok(());
+        println!("Working tree is not clean.");
+        let options = vec![
+            format!("park   - stash changes for `{current}`, restore when you switch back"),
+            format!("carry  - take changes along to `{target_name}` (possible conflicts)"),
+            "cancel - stay on the current branch".to_string(),
+        ];
+        match utils::select_one("Uncommitted changes option", &options)? {
+            Some(0) => {
+                let message = park_message(&current);
+                match git::stash_push(cwd, &message, true)? {
+                    Some(_) => println!("Parked changes for `{current}`."),
+                    None => println!("No changes needed parking."),
+                }
+            }
+            Some(1) => {}
+            _ => {
+                println!("Switch canceled due to uncommitted changes.");
+                return Ok(());
+            }
         }
     }
 
-    git::checkout_branch(cwd, target)?;
-    println!("Switched from `{current}` to `{target}`.");
+    match &targets[selected_idx] {
+        SwitchTarget::Local(name) => git::checkout_branch(cwd, name)?,
+        SwitchTarget::Remote(remote) => {
+            git::checkout_tracking_branch(cwd, &remote.full_name())?;
+            println!(
+                "Created local branch `{}` tracking `{}`.",
+                remote.branch,
+                remote.full_name()
+            );
+        }
+    }
+    println!("Switched from `{current}` to `{target_name}`.");
+    restore_parked_changes(cwd, &target_name)?;
     Ok(())
 }
+
+fn park_message(branch: &str) -> String {
+    format!("{PARK_PREFIX} {branch}")
+}
+
+/// Offers to bring back changes parked by a previous `wgit switch` away from `branch`.
+fn restore_parked_changes(cwd: &Path, branch: &str) -> Result<()> {
+    let message = park_message(branch);
+    let Some(parked) = git::list_stashes(cwd)?
+        .into_iter()
+        .find(|entry| entry.message == message)
+    else {
+        return Ok(());
+    };
+
+    println!(
+        "Found changes parked on `{branch}` {} ({}).",
+        parked.age, parked.stash_ref
+    );
+    if !git::is_clean_worktree(cwd)? {
+        println!("Working tree is not clean, so parked changes were left in the stash.");
+        println!("Restore them later with `wgit stash`.");
+        return Ok(());
+    }
+    if !utils::confirm(&format!("Restore parked changes for `{branch}`?"))? {
+        println!("Parked changes kept. Restore them later with `wgit stash`.");
+        return Ok(());
+    }
+
+    if git::stash_pop(cwd, &parked.stash_ref)? {
+        println!("Parked changes restored.");
+    } else {
+        println!("Parked changes conflict with the branch. The stash entry is kept.");
+        println!("Resolve the conflict markers, run `git restore --staged .`,");
+        println!("then drop the entry with `wgit stash`.");
+    }
+    Ok(())
+}
+
+fn local_label(branch: &git::TrackingBranch, current: &str) -> String {
+    let mut label = branch.name.clone();
+    if branch.name == current {
+        label.push_str(" (current)");
+    }
+    label.push_str(&format!(" | {}", branch.last_commit));
+    if branch.upstream_gone {
+        label.push_str(" | upstream gone");
+    } else if branch.upstream.is_some() {
+        if branch.ahead == 0 && branch.behind == 0 {
+            label.push_str(" | in sync");
+        } else {
+            label.push_str(&format!(
+                " | ahead {}, behind {}",
+                branch.ahead, branch.behind
+            ));
+        }
+    } else {
+        label.push_str(" | local only");
+    }
+    label
+}
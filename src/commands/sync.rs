@@ -1,45 +1,59 @@
+use crate::utils::ConflictChoice;
 use crate::{git, utils};
-use anyhow::Result;
-use std::path::Path;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
 
-pub fn run() -> Result<()> {
+const PREVIEW_LIMIT: usize = 10;
+const AUTO_STASH_MESSAGE: &str = "wgit sync auto-stash";
+
+struct SyncPlan {
+    remote: String,
+    remote_branch: String,
+    set_upstream: bool,
+    remote_exists: bool,
+    incoming: Vec<String>,
+    outgoing: Vec<String>,
+    needs_stash: bool,
+}
+
+impl SyncPlan {
+    fn rewrites_local_commits(&self) -> bool {
+        !self.incoming.is_empty() && !self.outgoing.is_empty()
+    }
+
+    fn needs_push(&self) -> bool {
+        !self.outgoing.is_empty() || !self.remote_exists || self.set_upstream
+    }
+
+    fn is_noop(&self) -> bool {
+        self.incoming.is_empty() && !self.needs_push()
+    }
+}
+
+pub fn run(fetch_only: bool, all_branches: bool) -> Result<()> {
     let cwd = Path::new(".");
+    if fetch_only {
+        return run_fetch_only(cwd);
+    }
+    if all_branches {
+        return run_all_branches(cwd);
+    }
+
     println!(
-        "Sync workflow: auto-stash local changes if needed, pull --rebase, push, then restore stash."
+        "Sync workflow: fetch, preview incoming/outgoing commits, then stash, pull --rebase, push and restore stash."
     );
     let branch = git::current_branch(cwd)?;
-    let mut stashed = false;
-
-    if git::has_uncommitted_changes(cwd)? {
-        println!("Detected uncommitted changes. Creating temporary stash...");
-        stashed = git::stash_push(cwd, "wgit sync auto-stash")?;
-        if stashed {
-            println!("Working tree stashed.");
-        } else {
-            println!("No changes needed stashing.");
-        }
+    let remotes = git::list_remotes(cwd)?;
+    if remotes.is_empty() {
+        println!("No remote configured. Skipping pull and push.");
+        return Ok(());
     }
+    git::fetch_all(cwd)?;
 
-    let upstream = git::upstream_remote_and_branch(cwd)?;
-    let mut push_remote: Option<String> = None;
-
-    let pull_result = match &upstream {
-        Some((remote, upstream_branch)) => {
-            println!("Using upstream branch: {remote}/{upstream_branch}");
-            if !git::remote_branch_exists(cwd, remote, upstream_branch)? {
-                println!("Remote branch does not exist yet (e.g. first push). Skipping pull.");
-                Ok(())
-            } else {
-                git::pull_rebase(cwd, None, None)
-            }
-        }
+    let (remote, remote_branch, set_upstream) = match git::upstream_remote_and_branch(cwd)? {
+        Some((remote, upstream_branch)) => (remote, upstream_branch, false),
         None => {
-            let remotes = git::list_remotes(cwd)?;
-            if remotes.is_empty() {
-                println!("No remote configured. Skipping pull and push.");
-                restore_stash_if_needed(cwd, stashed)?;
-                return Ok(());
-            }
             let labels: Vec<String> = remotes
                 .iter()
                 .map(|r| format!("{} -> {}", r.name, r.url))
@@ -47,65 +61,409 @@ pub fn run() -> Result<()> {
             let selected = utils::select_one("Select remote for first sync", &labels)?;
             let Some(index) = selected else {
                 println!("Sync canceled.");
-                restore_stash_if_needed(cwd, stashed)?;
                 return Ok(());
             };
-            let remote_name = remotes[index].name.clone();
-            push_remote = Some(remote_name.clone());
-            if !git::remote_branch_exists(cwd, &remote_name, &branch)? {
-                println!("Remote branch does not exist yet (e.g. first push). Skipping pull.");
-                Ok(())
-            } else {
-                git::pull_rebase(cwd, Some(&remote_name), Some(&branch))
-            }
+            (remotes[index].name.clone(), branch.clone(), true)
         }
     };
 
-    if let Err(error) = pull_result {
-        println!("Pull with rebase failed.");
-        let choice = utils::select_one(
-            "Resolve option",
-            &["abort".to_string(), "continue".to_string()],
-        )?;
-        match choice {
-            Some(0) => {
-                let _ = git::rebase_abort(cwd);
-                restore_stash_if_needed(cwd, stashed)?;
-                println!("Sync aborted and rebase state cleaned.");
-                return Ok(());
+    let plan = build_plan(cwd, remote, remote_branch, set_upstream)?;
+    print_plan(&branch, &plan);
+    if plan.is_noop() {
+        println!("Already in sync with remote. Nothing to do.");
+        return Ok(());
+    }
+    if !utils::confirm("Apply this sync plan?")? {
+        println!("Sync canceled. Nothing was changed.");
+        return Ok(());
+    }
+
+    let mut auto_stash = None;
+    if plan.needs_stash {
+        println!("Detected uncommitted changes. Creating temporary stash...");
+        auto_stash = git::stash_push(cwd, AUTO_STASH_MESSAGE, true)?;
+        match &auto_stash {
+            Some(id) => {
+                save_auto_stash_marker(cwd, id, &branch)?;
+                println!("Working tree stashed as {}.", short_id(id));
             }
-            Some(1) => {
-                println!("Resolve conflicts manually, then run:");
-                println!("  git add <files>");
-                println!("  git rebase --continue");
-                println!("Then run `wgit sync` again.");
-                println!("Original git error: {error:#}");
-                return Ok(());
+            None => println!("No changes needed stashing."),
+        }
+    }
+
+    let outcome = pull_and_push(cwd, &branch, &plan);
+    let Some(id) = auto_stash else {
+        return outcome.map(|_| ());
+    };
+
+    match outcome {
+        Ok(SyncOutcome::RebasePending) => {
+            println!(
+                "Auto-stash {} is kept. It will be restored on your next wgit run after the rebase finishes.",
+                short_id(&id)
+            );
+            Ok(())
+        }
+        Ok(_) => restore_auto_stash(cwd, &id),
+        Err(error) => {
+            if git::rebase_in_progress(cwd) {
+                println!(
+                    "Auto-stash {} is kept until the rebase finishes.",
+                    short_id(&id)
+                );
+            } else {
+                restore_auto_stash(cwd, &id)?;
             }
-            _ => {
-                println!("No option selected. Keeping current rebase state.");
-                println!("Original git error: {error:#}");
-                return Ok(());
+            Err(error)
+        }
+    }
+}
+
+enum SyncOutcome {
+    Completed,
+    Aborted,
+    RebasePending,
+}
+
+fn pull_and_push(cwd: &Path, branch: &str, plan: &SyncPlan) -> Result<SyncOutcome> {
+    if !plan.incoming.is_empty() {
+        let pull_result = if plan.set_upstream {
+            git::pull_rebase(cwd, Some(&plan.remote), Some(&plan.remote_branch))
+        } else {
+            git::pull_rebase(cwd, None, None)
+        };
+
+        if let Err(error) = pull_result {
+            println!("Pull with rebase failed.");
+            match utils::select_conflict_option("Resolve option")? {
+                Some(ConflictChoice::Abort) => {
+                    let _ = git::rebase_abort(cwd);
+                    println!("Sync aborted and rebase state cleaned.");
+                    return Ok(SyncOutcome::Aborted);
+                }
+                Some(ConflictChoice::Continue) => {
+                    println!("Resolve conflicts manually, then run:");
+                    println!("  git add <files>");
+                    println!("  git rebase --continue");
+                    println!("Then run `wgit sync` again.");
+                    println!("Original git error: {error:#}");
+                    return Ok(SyncOutcome::RebasePending);
+                }
+                None => {
+                    println!("No option selected. Keeping current rebase state.");
+                    println!("Original git error: {error:#}");
+                    return Ok(SyncOutcome::RebasePending);
+                }
             }
         }
     }
 
-    git::push_current(cwd, push_remote.as_deref(), &branch)?;
+    if plan.needs_push() {
+        let upstream_remote = plan.set_upstream.then_some(plan.remote.as_str());
+        git::push_current(cwd, upstream_remote, branch)?;
+    }
     println!("Pull and push completed.");
-    restore_stash_if_needed(cwd, stashed)?;
     println!("Sync completed.");
+    Ok(SyncOutcome::Completed)
+}
+
+fn build_plan(
+    cwd: &Path,
+    remote: String,
+    remote_branch: String,
+    set_upstream: bool,
+) -> Result<SyncPlan> {
+    let remote_exists = git::remote_branch_exists(cwd, &remote, &remote_branch)?;
+    let (incoming, outgoing) = if remote_exists {
+        let tracking = format!("{remote}/{remote_branch}");
+        (
+            git::list_commits(cwd, &[&format!("HEAD..{tracking}")])?,
+            git::list_commits(cwd, &[&format!("{tracking}..HEAD")])?,
+        )
+    } else {
+        (
+            Vec::new(),
+            git::list_commits(cwd, &["HEAD", "--not", "--remotes"])?,
+        )
+    };
+    // Only a rebase needs a clean tree; pushing alone leaves local edits untouched.
+    let needs_stash = !incoming.is_empty() && git::has_uncommitted_changes(cwd)?;
+
+    Ok(SyncPlan {
+        remote,
+        remote_branch,
+        set_upstream,
+        remote_exists,
+        incoming,
+        outgoing,
+        needs_stash,
+    })
+}
+
+fn print_plan(branch: &str, plan: &SyncPlan) {
+    println!();
+    println!(
+        "Sync plan for `{branch}` <-> `{}/{}`:",
+        plan.remote, plan.remote_branch
+    );
+    if !plan.remote_exists {
+        println!("  Remote branch does not exist yet (first push). Pull will be skipped.");
+    }
+    print_commit_list("Incoming commits (pull --rebase)", &plan.incoming);
+    print_commit_list("Outgoing commits (push)", &plan.outgoing);
+    println!(
+        "  Rebase rewrites local commits: {}",
+        yes_no(plan.rewrites_local_commits())
+    );
+    println!(
+        "  Uncommitted changes will be stashed: {}",
+        yes_no(plan.needs_stash)
+    );
+    if plan.set_upstream {
+        println!("  Upstream tracking will be set to `{}`.", plan.remote);
+    }
+    println!();
+}
+
+fn print_commit_list(title: &str, commits: &[String]) {
+    println!("  {title}: {}", commits.len());
+    for line in commits.iter().take(PREVIEW_LIMIT) {
+        println!("    {line}");
+    }
+    if commits.len() > PREVIEW_LIMIT {
+        println!("    ... and {} more", commits.len() - PREVIEW_LIMIT);
+    }
+}
+
+fn yes_no(value: bool) -> &'static str {
+    if value { "yes" } else { "no" }
+}
+
+fn run_fetch_only(cwd: &Path) -> Result<()> {
+    println!("Sync fetch-only workflow: download remote updates without changing local branches.");
+    if git::list_remotes(cwd)?.is_empty() {
+        println!("No remote configured. Nothing to fetch.");
+        return Ok(());
+    }
+    git::fetch_all(cwd)?;
+
+    let branch = git::current_branch(cwd)?;
+    match git::upstream_branch(cwd)? {
+        Some(upstream) => {
+            let (ahead, behind) = git::ahead_behind(cwd, &branch, upstream.trim())?;
+            println!("`{branch}` compared with `{}`:", upstream.trim());
+            println!("  Incoming commits: {behind}");
+            println!("  Outgoing commits: {ahead}");
+            if behind > 0 || ahead > 0 {
+                println!("Run `wgit sync` to apply these changes.");
+            }
+        }
+        None => println!("`{branch}` has no upstream yet. Run `wgit sync` to publish it."),
+    }
+    println!("Fetch completed. Local branches were not changed.");
+    Ok(())
+}
+
+enum BranchAction {
+    FastForward,
+    UpToDate,
+    Diverged,
+    UpstreamGone,
+}
+
+fn run_all_branches(cwd: &Path) -> Result<()> {
+    println!(
+        "Sync all-branches workflow: fetch, then fast-forward every local branch that tracks a remote."
+    );
+    if git::list_remotes(cwd)?.is_empty() {
+        println!("No remote configured. Nothing to sync.");
+        return Ok(());
+    }
+    git::fetch_all(cwd)?;
+
+    let current = git::current_branch(cwd)?;
+    let mut actions = Vec::new();
+    for branch in git::list_tracking_branches(cwd)? {
+        let Some(upstream) = branch.upstream.clone() else {
+            continue;
+        };
+        let action = if branch.upstream_gone {
+            BranchAction::UpstreamGone
+        } else {
+            let (ahead, behind) = git::ahead_behind(cwd, &branch.name, &upstream)?;
+            if behind == 0 {
+                BranchAction::UpToDate
+            } else if ahead > 0 {
+                BranchAction::Diverged
+            } else {
+                BranchAction::FastForward
+            }
+        };
+        actions.push((branch.name, upstream, action));
+    }
+
+    if actions.is_empty() {
+        println!("No local branch tracks a remote branch.");
+        return Ok(());
+    }
+
+    println!();
+    println!("Branch plan:");
+    for (name, upstream, action) in &actions {
+        let note = match action {
+            BranchAction::FastForward => "fast-forward",
+            BranchAction::UpToDate => "up to date",
+            BranchAction::Diverged => "diverged, skipped (run `wgit sync` on it)",
+            BranchAction::UpstreamGone => "upstream gone, skipped",
+        };
+        println!("  {name} <- {upstream}: {note}");
+    }
+    println!();
+
+    let pending = actions
+        .iter()
+        .filter(|(_, _, action)| matches!(action, BranchAction::FastForward))
+        .count();
+    if pending == 0 {
+        println!("Nothing to fast-forward.");
+        return Ok(());
+    }
+    if !utils::confirm(&format!("Fast-forward {pending} branch(es)?"))? {
+        println!("Sync canceled. Nothing was changed.");
+        return Ok(());
+    }
+
+    let mut failed = Vec::new();
+    for (name, upstream, action) in &actions {
+        if !matches!(action, BranchAction::FastForward) {
+            continue;
+        }
+        let ok = if name == &current {
+            git::merge_ff_only(cwd, upstream)?
+        } else {
+            git::fast_forward_branch(cwd, name, upstream)?
+        };
+        if !ok {
+            failed.push(name.clone());
+        }
+    }
+
+    if failed.is_empty() {
+        println!("Fast-forwarded {pending} branch(es).");
+    } else {
+        println!("Could not fast-forward: {}", failed.join(", "));
+        println!("Check uncommitted changes on the current branch, then retry.");
+    }
+    Ok(())
+}
+
+fn auto_stash_marker_path(cwd: &Path) -> Result<PathBuf> {
+    git::git_path(cwd, "wgit-sync-autostash")
+}
+
+fn save_auto_stash_marker(cwd: &Path, id: &str, branch: &str) -> Result<()> {
+    let path = auto_stash_marker_path(cwd)?;
+    fs::write(&path, format!("{id}\n{branch}\n"))
+        .with_context(|| format!("failed to write auto-stash marker: {}", path.display()))
+}
+
+fn read_auto_stash_marker(cwd: &Path) -> Result<Option<(String, String)>> {
+    let path = auto_stash_marker_path(cwd)?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(&path)
+        .with_context(|| format!("failed to read auto-stash marker: {}", path.display()))?;
+    let mut lines = content.lines().map(str::trim);
+    let id = lines.next().unwrap_or_default().to_string();
+    let branch = lines.next().unwrap_or_default().to_string();
+    if id.is_empty() {
+        clear_auto_stash_marker(cwd)?;
+        return Ok(None);
+    }
+    Ok(Some((id, branch)))
+}
+
+fn clear_auto_stash_marker(cwd: &Path) -> Result<()> {
+    let path = auto_stash_marker_path(cwd)?;
+    if path.exists() {
+        fs::remove_file(&path)
+            .with_context(|| format!("failed to remove auto-stash marker: {}", path.display()))?;
+    }
     Ok(())
 }
 
-fn restore_stash_if_needed(cwd: &Path, stashed: bool) -> Result<()> {
-    if !stashed {
+fn short_id(id: &str) -> &str {
+    &id[..id.len().min(7)]
+}
+
+/// Restores a sync auto-stash left behind by an interrupted run (Ctrl-C, a
+/// failed push, or the "continue" path of a conflicted rebase).
+pub fn restore_pending_auto_stash(cwd: &Path) -> Result<()> {
+    let Some((id, branch)) = read_auto_stash_marker(cwd)? else {
+        return Ok(());
+    };
+    if git::rebase_in_progress(cwd) {
+        println!(
+            "Note: `wgit sync` auto-stash {} is waiting for the current rebase to finish.",
+            short_id(&id)
+        );
+        return Ok(());
+    }
+
+    let current = git::current_branch(cwd)?;
+    if !branch.is_empty() && current != branch {
+        println!(
+            "Note: `wgit sync` auto-stash {} belongs to `{branch}`. Switch back to restore it.",
+            short_id(&id)
+        );
+        return Ok(());
+    }
+
+    println!("Found auto-stash from an interrupted `wgit sync`.");
+    restore_auto_stash(cwd, &id)
+}
+
+fn restore_auto_stash(cwd: &Path, id: &str) -> Result<()> {
+    let Some(stash_ref) = git::stash_ref_for_id(cwd, id)? else {
+        clear_auto_stash_marker(cwd)?;
+        println!(
+            "Auto-stash {} no longer exists (already restored or dropped).",
+            short_id(id)
+        );
+        return Ok(());
+    };
+
+    println!("Restoring stashed changes from {stash_ref}...");
+    // `stash pop` only drops the entry when it applied cleanly.
+    if git::stash_pop(cwd, &stash_ref)? {
+        clear_auto_stash_marker(cwd)?;
+        println!("Stash restored.");
         return Ok(());
     }
-    println!("Restoring stashed changes...");
-    if let Err(error) = git::stash_pop(cwd) {
-        println!("Failed to apply stash automatically. Resolve conflicts manually.");
-        return Err(error);
+
+    // The stash stays in the list; only stop retrying it on every run.
+    clear_auto_stash_marker(cwd)?;
+    guide_stash_conflict(cwd, &stash_ref, id)
+}
+
+fn guide_stash_conflict(cwd: &Path, stash_ref: &str, id: &str) -> Result<()> {
+    println!("[Safety Check] Restoring the auto-stash conflicts with the updated branch.");
+    println!(
+        "Your changes are safe: the stash is kept as {stash_ref} ({}).",
+        short_id(id)
+    );
+    let conflicted = git::unmerged_files(cwd)?;
+    if !conflicted.is_empty() {
+        println!("Files with conflicts:");
+        for path in &conflicted {
+            println!("  {path}");
+        }
     }
-    println!("Stash restored.");
+    println!("Next steps:");
+    println!("  1. Edit the files above and remove the conflict markers (<<<<<<<).");
+    println!("  2. Run `git restore --staged .` to keep the result as unstaged changes.");
+    println!("  3. Run `git stash drop {stash_ref}` once everything looks right.");
+    println!("To start over instead: `git reset --hard HEAD` then `git stash apply {stash_ref}`.");
     Ok(())
 }
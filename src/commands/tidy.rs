@@ -0,0 +1,211 @@
+use crate::commands::commit;
+use crate::policy::{self, Action};
+use crate::utils::{ConflictChoice, RebaseAction, RebaseStep};
+use crate::{config, convention, git, trailers, utils};
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn run() -> Result<()> {
+    let cwd = Path::new(".");
+    println!(
+        "Tidy workflow: list branch commits, edit a rebase plan (reorder, squash, fixup, drop, reword), then run it."
+    );
+    let branch = git::current_branch(cwd)?;
+    let branch_policy = policy::evaluate(cwd, &branch)?;
+    if let Some(reason) = branch_policy.denies(Action::Commit) {
+        println!("{reason}");
+        println!("Tidy rewrites history; run it on a work branch instead.");
+        return Ok(());
+    }
+    if git::rebase_in_progress(cwd) {
+        println!(
+            "A rebase is already in progress. Run `git rebase --continue` or `git rebase --abort` first."
+        );
+        return Ok(());
+    }
+    if !git::is_clean_worktree(cwd)? {
+        println!("Please commit or stash your changes before running tidy.");
+        return Ok(());
+    }
+
+    let parent = git::detect_parent_branch(cwd, &branch)?;
+    if parent == branch {
+        println!("Could not find a parent branch for `{branch}`. Nothing to tidy.");
+        return Ok(());
+    }
+    let base = git::branch_upstream(cwd, &parent)?.unwrap_or_else(|| parent.clone());
+    // Rebase onto the fork point, not the parent tip: tidy only rewrites the
+    // branch's own commits and never pulls in parent updates.
+    let fork_point = git::merge_base(cwd, &base, "HEAD")?;
+    let range = format!("{fork_point}..HEAD");
+    if git::count_merge_commits(cwd, &range)? > 0 {
+        println!(
+            "`{branch}` contains merge commits. Tidy replays history linearly and would drop them."
+        );
+        println!("Run `wgit refresh` with `sync.refresh_strategy = \"rebase\"` first.");
+        return Ok(());
+    }
+    let entries = git::list_commit_entries(cwd, &[&range])?;
+    if entries.is_empty() {
+        println!("`{branch}` has no commits of its own since `{base}`.");
+        return Ok(());
+    }
+
+    let steps = entries
+        .iter()
+        .map(|entry| RebaseStep {
+            action: RebaseAction::Pick,
+            hash: entry.hash.clone(),
+            subject: entry.subject.clone(),
+        })
+        .collect();
+    let prompt = format!(
+        "Tidy `{branch}`: {} commit(s) since `{base}`",
+        entries.len()
+    );
+    let Some(plan) = utils::edit_rebase_plan(&prompt, steps)? else {
+        println!("Tidy canceled.");
+        return Ok(());
+    };
+
+    let first_changed = plan
+        .iter()
+        .zip(&entries)
+        .position(|(step, entry)| step.action != RebaseAction::Pick || step.hash != entry.hash);
+    let Some(first_changed) = first_changed else {
+        println!("Plan unchanged. Nothing to do.");
+        return Ok(());
+    };
+    if !commit::confirm_rewrite_if_pushed(
+        cwd,
+        &branch_policy,
+        &entries[first_changed].hash,
+        "Tidy",
+    )? {
+        println!("Tidy canceled.");
+        return Ok(());
+    }
+
+    let mut message_files = Vec::new();
+    let mut todo = String::new();
+    for step in &plan {
+        match step.action {
+            RebaseAction::Reword => {
+                let Some(message) = compose_reword_message(cwd, step)? else {
+                    remove_files(&message_files);
+                    println!("Reword canceled. Tidy canceled; history was not changed.");
+                    return Ok(());
+                };
+                let path = temp_file(&format!("reword-{}", message_files.len()))?;
+                fs::write(&path, message).with_context(|| {
+                    format!("failed to write commit message file: {}", path.display())
+                })?;
+                todo.push_str(&format!("pick {}\n", step.hash));
+                todo.push_str(&format!(
+                    "exec git commit --amend --no-verify -F {}\n",
+                    git::shell_quote(&path.to_string_lossy())
+                ));
+                message_files.push(path);
+            }
+            action => todo.push_str(&format!("{} {}\n", action.as_str(), step.hash)),
+        }
+    }
+
+    println!();
+    println!("Tidy plan (oldest first):");
+    for step in &plan {
+        println!(
+            "  {:<7}{} {}",
+            step.action.as_str(),
+            &step.hash[..step.hash.len().min(7)],
+            step.subject
+        );
+    }
+    println!();
+    if !utils::confirm(&format!("Rewrite `{branch}` with this plan?"))? {
+        remove_files(&message_files);
+        println!("Tidy canceled.");
+        return Ok(());
+    }
+
+    let todo_path = temp_file("todo")?;
+    fs::write(&todo_path, todo)
+        .with_context(|| format!("failed to write rebase todo: {}", todo_path.display()))?;
+    message_files.push(todo_path.clone());
+    let todo_arg = todo_path.to_string_lossy().to_string();
+
+    if git::rebase_with_todo(cwd, &fork_point, &todo_arg)? {
+        remove_files(&message_files);
+        println!("`{branch}` tidied.");
+        if git::upstream_branch(cwd)?.is_some() {
+            println!("Publish the rewritten branch with `git push --force-with-lease`.");
+        }
+        return Ok(());
+    }
+
+    println!("Tidy stopped before finishing the plan (usually a conflict).");
+    match utils::select_conflict_option("Conflict option")? {
+        Some(ConflictChoice::Abort) => {
+            let _ = git::rebase_abort(cwd);
+            remove_files(&message_files);
+            println!("Tidy aborted and branch restored.");
+        }
+        Some(ConflictChoice::Continue) => {
+            // Reword steps still read their message files, so keep them.
+            println!("Resolve conflicts manually, then run:");
+            println!("  git add <files>");
+            println!("  git rebase --continue");
+        }
+        None => {
+            println!("No conflict option selected. Keeping current rebase state.");
+        }
+    }
+    Ok(())
+}
+
+/// Opens the structured editor prefilled from the commit being reworded.
+fn compose_reword_message(cwd: &Path, step: &RebaseStep) -> Result<Option<String>> {
+    let cfg = config::load_config(cwd)?;
+    let existing = git::commit_trailers(cwd, &step.hash)?;
+    let parsed =
+        convention::parse_commit_message(&git::commit_message(cwd, &step.hash)?, &existing);
+    let mut trailer_rows = trailers::editor_rows(cwd, &cfg)?;
+    commit::merge_existing_trailers(&mut trailer_rows, existing);
+
+    let commit_types = commit::commit_types();
+    let preselected = parsed
+        .commit_type
+        .and_then(|value| commit_types.iter().position(|item| *item == value));
+    let selected = utils::select_one_preselected(
+        &format!("Select commit type for `{}`", step.subject),
+        &commit_types,
+        preselected.unwrap_or(0),
+    )?;
+    let Some(index) = selected else {
+        return Ok(None);
+    };
+    let commit_type = &commit_types[index];
+    let Some(draft) = utils::edit_commit_message(commit_type, parsed.draft, trailer_rows)? else {
+        return Ok(None);
+    };
+    if draft.subject.trim().is_empty() {
+        bail!("commit subject cannot be empty");
+    }
+    commit::render_message(cwd, commit_type, &draft).map(Some)
+}
+
+fn temp_file(label: &str) -> Result<PathBuf> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("failed to generate temp filename")?
+        .as_nanos();
+    Ok(std::env::temp_dir().join(format!("wgit-tidy-{nanos}-{label}.txt")))
+}
+
+fn remove_files(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = fs::remove_file(path);
+    }
+}
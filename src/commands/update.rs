@@ -1,78 +1,405 @@
-use crate::git;
+use crate::archive::{self, AssetKind};
+use crate::config::{self, UpdateChannel, UpdateConfig, WgitConfig};
+use crate::github::Release;
+use crate::{git, github, release};
 use anyhow::{Context, Result, anyhow, bail};
 use reqwest::blocking::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::fs;
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
+use std::process::{self, Stdio};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-pub fn run() -> Result<()> {
+/// Exit code of `wgit update --check` when a newer release exists; clap
+/// already uses 2 for usage errors.
+const UPDATE_AVAILABLE_EXIT_CODE: i32 = 10;
+
+pub fn run(
+    channel: Option<String>,
+    version: Option<String>,
+    rollback: bool,
+    check: bool,
+) -> Result<()> {
     let cwd = Path::new(".");
+    let exe_path = std::env::current_exe().context("failed to locate current executable")?;
+    if rollback {
+        return rollback_executable(&exe_path);
+    }
     println!(
-        "Update workflow: check latest GitHub release, download matching asset, replace binary."
+        "Update workflow: check GitHub releases on the selected channel, download matching asset, verify, replace binary."
     );
-    let origin_url = git::origin_remote_url(cwd)?.ok_or_else(|| {
-        anyhow!("remote.origin.url not found; set origin to enable `wgit update`")
-    })?;
-    let repo = git::github_repo_slug_from_url(&origin_url)
-        .ok_or_else(|| anyhow!("origin remote is not a GitHub repository: {origin_url}"))?;
+    // Outside a repository there is no `.git/wgit.toml`; defaults apply.
+    let cfg = load_config_leniently(cwd);
+    let api_base = cfg.github_api_base;
+    let update_cfg = cfg.update;
+    let releases_url = releases_url(&update_cfg)?;
+    println!("Release source: {releases_url}");
 
     let current = normalize_version(env!("CARGO_PKG_VERSION"));
-    let release = fetch_latest_release(&repo)?;
-    let latest = normalize_version(&release.tag_name);
+    let pinned = version.is_some();
+    let release = match version {
+        Some(version) => {
+            let tag = if version.starts_with(|ch: char| ch.is_ascii_digit()) {
+                format!("v{version}")
+            } else {
+                version
+            };
+            github::fetch_release_by_tag(&releases_url, &tag, Some(&api_base))?
+        }
+        None => {
+            let channel = match channel.as_deref() {
+                Some(value) => UpdateChannel::parse(value)
+                    .ok_or_else(|| anyhow!("invalid channel `{value}`"))?,
+                None => update_cfg.channel,
+            };
+            println!("Channel: {}", channel.as_str());
+            latest_on_channel(&releases_url, channel, Some(&api_base), None)?
+        }
+    };
+    let target = normalize_version(&release.tag_name);
+    let ordering = compare_versions(&target, &current);
+    let kind = if release.prerelease {
+        " (pre-release)"
+    } else {
+        ""
+    };
 
-    if !is_newer(&latest, &current) {
-        println!("Already up to date (current: {current}, latest: {latest}).");
+    if check {
+        if ordering == Ordering::Greater {
+            println!("Update available: {current} -> {target}{kind}");
+            process::exit(UPDATE_AVAILABLE_EXIT_CODE);
+        }
+        println!("Up to date (current: {current}, available: {target}).");
         return Ok(());
     }
+    match ordering {
+        Ordering::Equal => {
+            println!("Already on {current}.");
+            return Ok(());
+        }
+        Ordering::Less if !pinned => {
+            println!("Already up to date (current: {current}, latest: {target}).");
+            return Ok(());
+        }
+        Ordering::Less => println!("Installing older release: {current} -> {target}{kind}"),
+        Ordering::Greater => println!("New version available: {current} -> {target}{kind}"),
+    }
 
-    let asset_name = platform_asset_name();
-    let Some(asset) = release.assets.iter().find(|a| a.name == asset_name) else {
+    let platform = Platform::current();
+    let candidates = platform.asset_candidates(update_cfg.asset_template.as_deref());
+    let Some(asset) = candidates
+        .iter()
+        .find_map(|name| release.assets.iter().find(|asset| asset.name == *name))
+    else {
+        let available: Vec<&str> = release
+            .assets
+            .iter()
+            .map(|asset| asset.name.as_str())
+            .collect();
         bail!(
-            "release `{}` has no compatible asset `{asset_name}`",
-            release.tag_name
+            "release `{}` has no asset for {}. tried: {}. available: {}. set [update] asset_template if the release uses another naming scheme",
+            release.tag_name,
+            platform.target(platform.libc),
+            candidates.join(", "),
+            if available.is_empty() {
+                "none".to_string()
+            } else {
+                available.join(", ")
+            }
         );
     };
 
-    println!("New version available: {current} -> {latest}");
-    println!("Downloading asset: {}", asset.name);
+    println!(
+        "Downloading asset: {} (target {})",
+        asset.name,
+        platform.target(platform.libc)
+    );
     let bytes = download_asset(&asset.browser_download_url)?;
+    // Checksums cover the published file, so archives are verified before extraction.
+    verify_download(&release, &asset.name, &bytes)?;
+    let kind = AssetKind::detect(&asset.name);
+    let binary_name = format!("wgit{}", std::env::consts::EXE_SUFFIX);
+    let binary = archive::extract_binary(kind, bytes, &binary_name)
+        .with_context(|| format!("failed to unpack release asset `{}`", asset.name))?;
+    if kind != AssetKind::Binary {
+        println!("Extracted {binary_name} from {}.", asset.name);
+    }
 
-    let exe_path = std::env::current_exe().context("failed to locate current executable")?;
-    replace_executable(&exe_path, &bytes)?;
-    println!("Update completed. Restart wgit to use version {latest}.");
+    replace_executable(&exe_path, &binary)?;
+    println!("Update completed. Restart wgit to use version {target}.");
+    println!("The previous binary was kept; undo with `wgit update --rollback`.");
     Ok(())
 }
 
-#[derive(Debug, Deserialize)]
-struct ReleaseAsset {
-    name: String,
-    browser_download_url: String,
+/// A config this build cannot read (a newer `version`, a schema or lock
+/// error) must not block the update that may fix it: fall back to the
+/// global config, then to built-in defaults, and say so.
+fn load_config_leniently(cwd: &Path) -> WgitConfig {
+    let error = match config::load_config(cwd) {
+        Ok(cfg) => return cfg,
+        Err(error) => error,
+    };
+    eprintln!("Warning: ignoring repository config: {error:#}");
+    match config::load_global_config() {
+        Ok(cfg) => cfg,
+        Err(error) => {
+            eprintln!("Warning: ignoring global config: {error:#}");
+            eprintln!("Using environment variables and built-in defaults for the update source.");
+            WgitConfig::default()
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
-struct ReleaseResponse {
-    tag_name: String,
-    assets: Vec<ReleaseAsset>,
+/// Stable uses GitHub's `latest`; the pre-release channel picks the highest
+/// version among published releases, pre-releases included.
+fn latest_on_channel(
+    releases_url: &str,
+    channel: UpdateChannel,
+    api_base: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<Release> {
+    match channel {
+        UpdateChannel::Stable => github::fetch_latest_release(releases_url, api_base, timeout),
+        UpdateChannel::Prerelease => github::fetch_releases(releases_url, api_base, timeout)?
+            .into_iter()
+            .filter(|release| !release.draft)
+            .max_by(|left, right| {
+                compare_versions(
+                    &normalize_version(&left.tag_name),
+                    &normalize_version(&right.tag_name),
+                )
+            })
+            .ok_or_else(|| anyhow!("no published releases found at {releases_url}")),
+    }
 }
 
-fn fetch_latest_release(repo: &str) -> Result<ReleaseResponse> {
-    let url = format!("https://api.github.com/repos/{repo}/releases/latest");
-    let client = Client::builder()
-        .build()
-        .context("failed to build HTTP client")?;
+/// Set to any non-empty value to turn off the background update notice.
+const NO_UPDATE_CHECK_ENV: &str = "WGIT_NO_UPDATE_CHECK";
+const CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+const CHECK_CACHE_FILE: &str = "update-check.toml";
 
-    let response = client
-        .get(url)
-        .header("User-Agent", "wgit-updater")
-        .header("Accept", "application/vnd.github+json")
-        .send()
-        .context("failed to request latest release")?
-        .error_for_status()
-        .context("GitHub API returned non-success status")?;
+/// Result of the last background check, kept in the user's cache directory.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct CheckCache {
+    /// Releases endpoint and channel the result belongs to.
+    source: String,
+    /// Unix seconds of the last check attempt.
+    checked_at: u64,
+    latest: Option<String>,
+}
+
+/// Runs after a successful command: prints a one-line notice when the
+/// cached latest release is newer, and refreshes a stale cache in a
+/// detached `wgit update --background-check` so the command never waits
+/// on the network. Every failure here is silent.
+pub fn notify_if_outdated() {
+    if std::env::var(NO_UPDATE_CHECK_ENV).is_ok_and(|value| !value.trim().is_empty())
+        || !std::io::stderr().is_terminal()
+    {
+        return;
+    }
+    // Only global config: a cloned repository must not pick the release source.
+    let Ok(cfg) = config::load_global_config() else {
+        return;
+    };
+    if !cfg.update.check {
+        return;
+    }
+    let (Some(path), Ok(source)) = (check_cache_path(), check_source(&cfg.update)) else {
+        return;
+    };
+    let cache = read_check_cache(&path).filter(|cache| cache.source == source);
+    let now = unix_now();
+    let interval = cfg.update.check_interval_hours.saturating_mul(3600);
+    let stale = cache
+        .as_ref()
+        .is_none_or(|cache| now.saturating_sub(cache.checked_at) >= interval);
+    let latest = cache.and_then(|cache| cache.latest);
+    if stale {
+        // Stamp the attempt first so an offline check is not retried before the interval.
+        let stamped = CheckCache {
+            source,
+            checked_at: now,
+            latest: latest.clone(),
+        };
+        if write_check_cache(&path, &stamped).is_ok() {
+            spawn_background_check();
+        }
+    }
+
+    let current = normalize_version(env!("CARGO_PKG_VERSION"));
+    if let Some(latest) = latest
+        && compare_versions(&latest, &current) == Ordering::Greater
+    {
+        eprintln!(
+            "A new wgit release is available: {current} -> {latest}. Run `wgit update` to install it."
+        );
+    }
+}
+
+/// Body of `wgit update --background-check`: queries the release endpoint
+/// with a short timeout and records the latest version in the cache. Reads
+/// global config and env only and never sends `GITHUB_TOKEN`.
+pub fn refresh_check_cache() -> Result<()> {
+    let cfg = config::load_global_config()?;
+    let source = check_source(&cfg.update)?;
+    let path = check_cache_path().ok_or_else(|| anyhow!("no user cache directory"))?;
+    let releases_url = releases_url(&cfg.update)?;
+    let release = latest_on_channel(&releases_url, cfg.update.channel, None, Some(CHECK_TIMEOUT))?;
+    write_check_cache(
+        &path,
+        &CheckCache {
+            source,
+            checked_at: unix_now(),
+            latest: Some(normalize_version(&release.tag_name)),
+        },
+    )
+}
+
+fn spawn_background_check() {
+    let Ok(exe_path) = std::env::current_exe() else {
+        return;
+    };
+    let mut command = process::Command::new(exe_path);
+    command
+        .args(["update", "--background-check"])
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Its own process group keeps the check alive when the terminal hangs up.
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    let _ = command.spawn();
+}
+
+fn check_source(update: &UpdateConfig) -> Result<String> {
+    Ok(format!(
+        "{} {}",
+        releases_url(update)?,
+        update.channel.as_str()
+    ))
+}
+
+/// `$XDG_CACHE_HOME/wgit`, else the platform cache directory
+/// (`~/Library/Caches`, `%LOCALAPPDATA%`, or `~/.cache`).
+fn check_cache_path() -> Option<PathBuf> {
+    let from_env = |key: &str| {
+        std::env::var_os(key)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+    };
+    let base = from_env("XDG_CACHE_HOME").or_else(|| {
+        if cfg!(target_os = "windows") {
+            from_env("LOCALAPPDATA")
+        } else if cfg!(target_os = "macos") {
+            from_env("HOME").map(|home| home.join("Library").join("Caches"))
+        } else {
+            from_env("HOME").map(|home| home.join(".cache"))
+        }
+    })?;
+    Some(base.join("wgit").join(CHECK_CACHE_FILE))
+}
+
+fn read_check_cache(path: &Path) -> Option<CheckCache> {
+    toml::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+fn write_check_cache(path: &Path, cache: &CheckCache) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("failed to create cache directory: {}", parent.display()))?;
+    }
+    let content = toml::to_string(cache).context("failed to serialize update check cache")?;
+    fs::write(path, content)
+        .with_context(|| format!("failed to write update check cache: {}", path.display()))
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// wgit's own release repository, embedded when builds set `WGIT_RELEASE_REPO`.
+const RELEASE_REPO: Option<&str> = option_env!("WGIT_RELEASE_REPO");
+
+/// Resolves the releases endpoint. Precedence: `WGIT_UPDATE_BASE_URL`,
+/// `update.base_url`, then the GitHub repo from `WGIT_UPDATE_REPO`,
+/// `update.repo`, the embedded `WGIT_RELEASE_REPO`, or Cargo's `repository`.
+/// The current project's origin is never used.
+fn releases_url(update: &UpdateConfig) -> Result<String> {
+    let from_env = |key: &str| {
+        std::env::var(key)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+    if let Some(base_url) = from_env("WGIT_UPDATE_BASE_URL").or_else(|| update.base_url.clone()) {
+        return Ok(base_url.trim_end_matches('/').to_string());
+    }
+    let repo = from_env("WGIT_UPDATE_REPO")
+        .or_else(|| update.repo.clone())
+        .or_else(|| RELEASE_REPO.map(str::to_string))
+        .or_else(|| git::github_repo_slug_from_url(env!("CARGO_PKG_REPOSITORY")));
+    let Some(repo) = repo else {
+        bail!(
+            "this build has no embedded release repository. set WGIT_UPDATE_REPO=owner/name or [update] repo, or WGIT_UPDATE_BASE_URL for a mirror"
+        );
+    };
+    if !config::is_repo_slug(&repo) {
+        bail!("invalid release repository `{repo}`. expected `owner/name`");
+    }
+    Ok(format!("{}/repos/{repo}/releases", github::GITHUB_API))
+}
+
+/// Hex ed25519 public key for `SHA256SUMS.sig`, embedded when release
+/// builds set `WGIT_UPDATE_PUBLIC_KEY`.
+const UPDATE_PUBLIC_KEY: Option<&str> = option_env!("WGIT_UPDATE_PUBLIC_KEY");
+
+/// Refuses the download unless it matches the release's `SHA256SUMS`, and
+/// unless `SHA256SUMS` is signed when this build embeds a public key.
+fn verify_download(release: &Release, asset_name: &str, bytes: &[u8]) -> Result<()> {
+    let find = |name: &str| release.assets.iter().find(|asset| asset.name == name);
+    let Some(sums_asset) = find(release::CHECKSUMS_NAME) else {
+        bail!(
+            "checksum check failed: release `{}` has no {} asset. refusing to replace the executable",
+            release.tag_name,
+            release::CHECKSUMS_NAME
+        );
+    };
+    let sums = download_asset(&sums_asset.browser_download_url)?;
+
+    match UPDATE_PUBLIC_KEY.filter(|key| !key.trim().is_empty()) {
+        Some(public_key) => {
+            let Some(signature_asset) = find(release::SIGNATURE_NAME) else {
+                bail!(
+                    "signature check failed: release `{}` has no {} asset, but this build only accepts signed releases",
+                    release.tag_name,
+                    release::SIGNATURE_NAME
+                );
+            };
+            let signature = download_asset(&signature_asset.browser_download_url)?;
+            release::verify_signature(public_key, &sums, &String::from_utf8_lossy(&signature))?;
+            println!("Signature check passed ({}).", release::SIGNATURE_NAME);
+        }
+        None => println!("Signature check skipped: this build has no embedded public key."),
+    }
 
-    response
-        .json::<ReleaseResponse>()
-        .context("failed to parse release response")
+    let sums = String::from_utf8(sums).with_context(|| {
+        format!(
+            "checksum check failed: {} is not valid UTF-8",
+            release::CHECKSUMS_NAME
+        )
+    })?;
+    release::verify_checksum(&sums, asset_name, bytes)?;
+    println!("Checksum check passed for {asset_name}.");
+    Ok(())
 }
 
 fn download_asset(url: &str) -> Result<Vec<u8>> {
@@ -98,29 +425,154 @@ fn normalize_version(raw: &str) -> String {
         .to_string()
 }
 
-fn is_newer(candidate: &str, current: &str) -> bool {
-    let parse = |value: &str| -> Vec<u64> {
-        value
+/// Orders `1.2.3` style versions; a pre-release (`1.2.3-rc.1`) sorts
+/// before its release, and pre-release identifiers compare numerically
+/// when both are numbers.
+fn compare_versions(left: &str, right: &str) -> Ordering {
+    let split = |value: &str| -> (Vec<u64>, Option<String>) {
+        let value = value.split('+').next().unwrap_or_default();
+        let (core, pre) = match value.split_once('-') {
+            Some((core, pre)) => (core, Some(pre.to_string())),
+            None => (value, None),
+        };
+        let numbers = core
             .split('.')
             .map(|part| part.parse::<u64>().unwrap_or(0))
-            .collect()
+            .collect();
+        (numbers, pre)
     };
+    let (mut left_core, left_pre) = split(left);
+    let (mut right_core, right_pre) = split(right);
+    let max_len = left_core.len().max(right_core.len());
+    left_core.resize(max_len, 0);
+    right_core.resize(max_len, 0);
+    left_core
+        .cmp(&right_core)
+        .then_with(|| match (&left_pre, &right_pre) {
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(_), None) => Ordering::Less,
+            (Some(left), Some(right)) => compare_prerelease(left, right),
+        })
+}
 
-    let mut left = parse(candidate);
-    let mut right = parse(current);
-    let max_len = left.len().max(right.len());
-    left.resize(max_len, 0);
-    right.resize(max_len, 0);
-    left > right
+fn compare_prerelease(left: &str, right: &str) -> Ordering {
+    let mut left_parts = left.split('.');
+    let mut right_parts = right.split('.');
+    loop {
+        match (left_parts.next(), right_parts.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(left), Some(right)) => {
+                let ordering = match (left.parse::<u64>(), right.parse::<u64>()) {
+                    (Ok(left), Ok(right)) => left.cmp(&right),
+                    (Ok(_), Err(_)) => Ordering::Less,
+                    (Err(_), Ok(_)) => Ordering::Greater,
+                    (Err(_), Err(_)) => left.cmp(right),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
 }
 
-fn platform_asset_name() -> &'static str {
-    if cfg!(target_os = "windows") {
-        "wgit-windows-amd64.exe"
-    } else if cfg!(target_os = "macos") {
-        "wgit-macos-amd64"
-    } else {
-        "wgit-linux-amd64"
+/// Asset name stems tried when `update.asset_template` is unset: the
+/// target triple first, then the older `wgit-linux-amd64` style.
+const DEFAULT_ASSET_TEMPLATES: [&str; 2] = ["wgit-{target}", "wgit-{os}-{arch_alias}"];
+
+/// The platform this binary was built for, as named in release assets.
+struct Platform {
+    os: &'static str,
+    arch: &'static str,
+    libc: &'static str,
+}
+
+impl Platform {
+    fn current() -> Self {
+        let libc = if cfg!(target_env = "musl") {
+            "musl"
+        } else if cfg!(target_env = "msvc") {
+            "msvc"
+        } else if cfg!(target_env = "gnu") {
+            "gnu"
+        } else {
+            ""
+        };
+        Self {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            libc,
+        }
+    }
+
+    /// Rust target triple, e.g. `x86_64-unknown-linux-gnu` or `aarch64-apple-darwin`.
+    fn target(&self, libc: &str) -> String {
+        let arch = self.arch;
+        match self.os {
+            "macos" => format!("{arch}-apple-darwin"),
+            "windows" => format!("{arch}-pc-windows-{libc}"),
+            "linux" => format!("{arch}-unknown-linux-{libc}"),
+            os => format!("{arch}-unknown-{os}"),
+        }
+    }
+
+    /// Libc variants worth trying: a glibc build also runs a static musl binary.
+    fn libc_variants(&self) -> Vec<&'static str> {
+        if self.os == "linux" && self.libc == "gnu" {
+            vec!["gnu", "musl"]
+        } else {
+            vec![self.libc]
+        }
+    }
+
+    fn arch_alias(&self) -> &'static str {
+        match self.arch {
+            "x86_64" => "amd64",
+            "aarch64" => "arm64",
+            "x86" => "386",
+            arch => arch,
+        }
+    }
+
+    fn render(&self, template: &str, libc: &str) -> String {
+        template
+            .replace("{target}", &self.target(libc))
+            .replace("{os}", self.os)
+            .replace("{arch_alias}", self.arch_alias())
+            .replace("{arch}", self.arch)
+            .replace("{libc}", libc)
+            .replace("{exe}", std::env::consts::EXE_SUFFIX)
+    }
+
+    /// Asset names to look for, most specific first. Each rendered stem is
+    /// tried bare, as `.exe` on Windows, and as a `.tar.gz`, `.tgz` or `.zip` archive.
+    fn asset_candidates(&self, template: Option<&str>) -> Vec<String> {
+        let templates = match template {
+            Some(template) => vec![template],
+            None => DEFAULT_ASSET_TEMPLATES.to_vec(),
+        };
+        let mut suffixes = vec![""];
+        if self.os == "windows" {
+            suffixes.push(".exe");
+        }
+        suffixes.extend([".tar.gz", ".tgz", ".zip"]);
+
+        let mut candidates: Vec<String> = Vec::new();
+        for template in templates {
+            for libc in self.libc_variants() {
+                let stem = self.render(template, libc);
+                for suffix in &suffixes {
+                    let name = format!("{stem}{suffix}");
+                    if !candidates.contains(&name) {
+                        candidates.push(name);
+                    }
+                }
+            }
+        }
+        candidates
     }
 }
 
@@ -138,6 +590,11 @@ fn replace_executable(exe_path: &Path, bytes: &[u8]) -> Result<()> {
         .with_context(|| format!("failed to write downloaded binary: {}", new_path.display()))?;
     set_executable_permission(&new_path)?;
 
+    // Only one previous binary is kept for `--rollback`.
+    if backup_path.exists() {
+        fs::remove_file(&backup_path)
+            .with_context(|| format!("failed to remove old backup: {}", backup_path.display()))?;
+    }
     fs::rename(exe_path, &backup_path).with_context(|| {
         format!(
             "failed to backup current binary: {} -> {}",
@@ -154,7 +611,47 @@ fn replace_executable(exe_path: &Path, bytes: &[u8]) -> Result<()> {
         ));
     }
 
-    let _ = fs::remove_file(&backup_path);
+    Ok(())
+}
+
+/// Swaps the running binary with the `.bak` kept by the last update, so a
+/// second rollback returns to the newer binary.
+fn rollback_executable(exe_path: &Path) -> Result<()> {
+    println!("Rollback workflow: swap the current binary with the one kept by the last update.");
+    let parent = exe_path.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = exe_path
+        .file_name()
+        .ok_or_else(|| anyhow!("invalid executable path: {}", exe_path.display()))?
+        .to_string_lossy()
+        .to_string();
+    let backup_path = temp_path(parent, &file_name, ".bak");
+    if !backup_path.is_file() {
+        bail!(
+            "no previous binary found at {}. nothing to roll back to",
+            backup_path.display()
+        );
+    }
+    let swap_path = temp_path(parent, &file_name, ".swap");
+    fs::rename(exe_path, &swap_path).with_context(|| {
+        format!(
+            "failed to move current binary aside: {}",
+            exe_path.display()
+        )
+    })?;
+    if let Err(error) = fs::rename(&backup_path, exe_path) {
+        let _ = fs::rename(&swap_path, exe_path);
+        return Err(anyhow!(
+            "failed to restore previous binary: {error}. current binary kept"
+        ));
+    }
+    fs::rename(&swap_path, &backup_path).with_context(|| {
+        format!(
+            "rolled back, but failed to keep the newer binary at {}",
+            backup_path.display()
+        )
+    })?;
+    println!("Rolled back to the previous binary. Run `wgit --version` to confirm.");
+    println!("Run `wgit update --rollback` again to return to the newer one.");
     Ok(())
 }
 
@@ -178,3 +675,97 @@ fn set_executable_permission(_path: &Path) -> Result<()> {
 fn temp_path(parent: &Path, file_name: &str, suffix: &str) -> PathBuf {
     parent.join(format!("{file_name}{suffix}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Platform, compare_versions, normalize_version};
+    use std::cmp::Ordering;
+
+    const LINUX_GNU: Platform = Platform {
+        os: "linux",
+        arch: "x86_64",
+        libc: "gnu",
+    };
+
+    #[test]
+    fn default_candidates_try_the_target_triple_first() {
+        let candidates = LINUX_GNU.asset_candidates(None);
+        assert_eq!(
+            candidates[..5],
+            [
+                "wgit-x86_64-unknown-linux-gnu",
+                "wgit-x86_64-unknown-linux-gnu.tar.gz",
+                "wgit-x86_64-unknown-linux-gnu.tgz",
+                "wgit-x86_64-unknown-linux-gnu.zip",
+                "wgit-x86_64-unknown-linux-musl",
+            ]
+        );
+        assert!(candidates.contains(&"wgit-linux-amd64.tar.gz".to_string()));
+        assert!(!candidates.iter().any(|name| name.ends_with(".exe")));
+    }
+
+    #[test]
+    fn windows_and_macos_candidates() {
+        let windows = Platform {
+            os: "windows",
+            arch: "x86_64",
+            libc: "msvc",
+        };
+        let candidates = windows.asset_candidates(None);
+        assert_eq!(candidates[1], "wgit-x86_64-pc-windows-msvc.exe");
+        assert!(candidates.contains(&"wgit-windows-amd64.zip".to_string()));
+
+        let macos = Platform {
+            os: "macos",
+            arch: "aarch64",
+            libc: "",
+        };
+        let candidates = macos.asset_candidates(None);
+        assert_eq!(candidates[0], "wgit-aarch64-apple-darwin");
+        assert!(candidates.contains(&"wgit-macos-arm64".to_string()));
+    }
+
+    #[test]
+    fn template_placeholders_are_rendered_without_duplicates() {
+        let candidates = LINUX_GNU.asset_candidates(Some("tool_{os}_{arch_alias}"));
+        assert_eq!(
+            candidates,
+            [
+                "tool_linux_amd64",
+                "tool_linux_amd64.tar.gz",
+                "tool_linux_amd64.tgz",
+                "tool_linux_amd64.zip",
+            ]
+        );
+        let candidates = LINUX_GNU.asset_candidates(Some("tool-{arch}-{libc}"));
+        assert_eq!(candidates[0], "tool-x86_64-gnu");
+        assert_eq!(candidates[4], "tool-x86_64-musl");
+    }
+
+    #[test]
+    fn versions_compare_numerically_with_missing_parts_as_zero() {
+        assert_eq!(compare_versions("1.10.0", "1.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2.0+build.5", "1.2.0"), Ordering::Equal);
+        assert_eq!(
+            compare_versions(&normalize_version("v2.0.0"), &normalize_version(" V1.9.0 ")),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn prereleases_sort_before_their_release() {
+        assert_eq!(compare_versions("1.2.0-rc.1", "1.2.0"), Ordering::Less);
+        assert_eq!(
+            compare_versions("1.2.0-rc.10", "1.2.0-rc.2"),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare_versions("1.2.0-alpha", "1.2.0-beta"),
+            Ordering::Less
+        );
+        assert_eq!(compare_versions("1.2.0-1", "1.2.0-alpha"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.0-rc", "1.2.0-rc.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.2.1-rc.1", "1.2.0"), Ordering::Greater);
+    }
+}
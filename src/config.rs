@@ -1,33 +1,487 @@
-use anyhow::{Context, Result};
+use crate::settings;
+use anyhow::{Context, Result, bail};
+use regex::Regex;
 use serde::Deserialize;
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug)]
 pub struct WgitConfig {
+    /// Exact names or glob patterns (`release/*`), evaluated by `policy`.
     pub protected_branches: Vec<String>,
+    /// Rules keyed by the protected pattern they apply to.
+    pub branch_rules: BTreeMap<String, BranchRules>,
     pub auto_bootstrap_commit_on_start: bool,
     pub require_double_confirm_for_hard_reset: bool,
+    pub refresh_strategy: RefreshStrategy,
+    pub stale_branch_days: u64,
+    pub branch_naming: BranchNaming,
+    pub issue: IssueConfig,
+    pub commit: CommitConfig,
+    /// GitHub API root for issue lookups and pull requests. Point it at a local stub for testing.
+    pub github_api_base: String,
+    pub backport: BackportConfig,
+    pub flow: FlowConfig,
+    pub release: ReleaseConfig,
+    pub update: UpdateConfig,
+}
+
+/// `[update]` overrides for where `wgit update` finds wgit's own releases.
+#[derive(Clone, Debug)]
+pub struct UpdateConfig {
+    /// `owner/name` on GitHub, replacing the repository embedded at build time.
+    pub repo: Option<String>,
+    /// Releases endpoint of a mirror; `{base_url}/latest` must return GitHub release JSON.
+    pub base_url: Option<String>,
+    pub channel: UpdateChannel,
+    /// Asset name stem such as `wgit-{target}`; see [`ASSET_PLACEHOLDERS`].
+    pub asset_template: Option<String>,
+    /// Background "new version available" notice after commands.
+    pub check: bool,
+    pub check_interval_hours: u64,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            repo: None,
+            base_url: None,
+            channel: UpdateChannel::default(),
+            asset_template: None,
+            check: true,
+            check_interval_hours: 24,
+        }
+    }
+}
+
+/// Placeholders `update.asset_template` may use.
+pub const ASSET_PLACEHOLDERS: [&str; 6] = ["target", "os", "arch", "arch_alias", "libc", "exe"];
+
+/// Which releases `wgit update` considers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UpdateChannel {
+    /// Published releases only (`releases/latest`).
+    #[default]
+    Stable,
+    /// The newest release including pre-releases.
+    Prerelease,
+}
+
+impl UpdateChannel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Prerelease => "prerelease",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "stable" => Some(Self::Stable),
+            "prerelease" | "pre-release" => Some(Self::Prerelease),
+            _ => None,
+        }
+    }
+}
+
+/// `[release]` settings applied when `finish` tags a release.
+#[derive(Clone, Debug, Default)]
+pub struct ReleaseConfig {
+    /// Files whose version is bumped and committed before the tag.
+    pub version_files: Vec<VersionFile>,
+    /// Build artifacts uploaded by `wgit release publish`; `*` matches within a file name.
+    pub assets: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct VersionFile {
+    /// Path relative to the repository root.
+    pub path: String,
+    pub format: VersionFormat,
+}
+
+/// Manifest format, inferred from the file name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VersionFormat {
+    /// `version` in `[package]` or `[workspace.package]`; `Cargo.lock` follows.
+    Cargo,
+    /// Top-level `"version"` key.
+    PackageJson,
+    /// `version` in `[project]` or `[tool.poetry]`.
+    PyProject,
+    /// The whole file is the version, e.g. `VERSION`.
+    Plain,
+}
+
+impl VersionFormat {
+    fn detect(path: &str) -> Option<Self> {
+        let name = Path::new(path).file_name()?.to_str()?;
+        match name {
+            "Cargo.toml" => Some(Self::Cargo),
+            "package.json" => Some(Self::PackageJson),
+            "pyproject.toml" => Some(Self::PyProject),
+            "VERSION" => Some(Self::Plain),
+            _ if name.ends_with(".txt") => Some(Self::Plain),
+            _ => None,
+        }
+    }
+}
+
+/// `[flow]` branching model used by `start` and `finish` for release and hotfix branches.
+#[derive(Clone, Debug)]
+pub struct FlowConfig {
+    pub model: FlowModel,
+    /// Branch that receives releases and hotfixes. `None` falls back to `main` or `master`.
+    pub primary_branch: Option<String>,
+    /// Integration branch for git-flow; releases are cut from it and merged back into it.
+    pub develop_branch: String,
+}
+
+impl Default for FlowConfig {
+    fn default() -> Self {
+        Self {
+            model: FlowModel::Github,
+            primary_branch: None,
+            develop_branch: "develop".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlowModel {
+    /// One long-lived branch: everything is cut from and finished into it.
+    Github,
+    /// `develop` plus `main`: releases and hotfixes are merged back into integration.
+    Gitflow,
+}
+
+impl FlowModel {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Github => "github",
+            Self::Gitflow => "gitflow",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "github" => Some(Self::Github),
+            "gitflow" | "git-flow" => Some(Self::Gitflow),
+            _ => None,
+        }
+    }
+}
+
+/// `[backport]` settings for `wgit backport`.
+#[derive(Clone, Debug)]
+pub struct BackportConfig {
+    /// Branch patterns pre-selected as targets.
+    pub targets: Vec<String>,
+    pub branch_prefix: String,
+}
+
+impl Default for BackportConfig {
+    fn default() -> Self {
+        Self {
+            targets: vec!["main".to_string(), "release/*".to_string()],
+            branch_prefix: "backport".to_string(),
+        }
+    }
+}
+
+/// `[commit]` trailer settings for the commit editor.
+#[derive(Clone, Debug, Default)]
+pub struct CommitConfig {
+    /// Enforce DCO: `Signed-off-by` is always added and cannot be switched off.
+    pub require_signoff: bool,
+    /// Extra `(key, default value)` trailers offered in the editor.
+    pub trailers: Vec<(String, String)>,
+}
+
+/// `[branch]` naming rules applied by `wgit start`.
+#[derive(Clone, Debug)]
+pub struct BranchNaming {
+    pub types: Vec<String>,
+    pub slug_pattern: Regex,
+    pub max_length: usize,
+    pub require_issue_key: bool,
+    pub issue_key_pattern: Regex,
+}
+
+impl Default for BranchNaming {
+    fn default() -> Self {
+        Self {
+            types: ["feature", "bugfix", "hotfix", "release"]
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            slug_pattern: Regex::new(DEFAULT_SLUG_PATTERN).expect("default slug pattern is valid"),
+            max_length: 64,
+            require_issue_key: false,
+            issue_key_pattern: Regex::new(DEFAULT_ISSUE_KEY_PATTERN)
+                .expect("default issue key pattern is valid"),
+        }
+    }
+}
+
+/// `[issue]` settings for linking branches and commits to tickets.
+#[derive(Clone, Debug)]
+pub struct IssueConfig {
+    pub placement: IssuePlacement,
+    pub trailer_key: String,
+}
+
+impl Default for IssueConfig {
+    fn default() -> Self {
+        Self {
+            placement: IssuePlacement::Trailer,
+            trailer_key: "Refs".to_string(),
+        }
+    }
+}
+
+/// Where `wgit commit` writes the branch's issue reference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IssuePlacement {
+    /// Append a `Refs: PROJ-123` trailer.
+    Trailer,
+    /// Use the reference as the commit scope: `feat(PROJ-123): ...`.
+    Scope,
+    None,
+}
+
+impl IssuePlacement {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "trailer" => Some(Self::Trailer),
+            "scope" => Some(Self::Scope),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+const DEFAULT_SLUG_PATTERN: &str = "^[a-z0-9._-]+$";
+const DEFAULT_ISSUE_KEY_PATTERN: &str = "[A-Z][A-Z0-9]+-[0-9]+";
+
+/// How `wgit refresh` brings parent branch commits into the current branch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RefreshStrategy {
+    Rebase,
+    Merge,
+}
+
+impl RefreshStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Rebase => "rebase",
+            Self::Merge => "merge",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "rebase" => Some(Self::Rebase),
+            "merge" => Some(Self::Merge),
+            _ => None,
+        }
+    }
+}
+
+/// How `wgit finish` combines a branch into its parent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeStrategy {
+    Squash,
+    Merge,
+}
+
+impl MergeStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Squash => "squash",
+            Self::Merge => "merge",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "squash" => Some(Self::Squash),
+            "merge" => Some(Self::Merge),
+            _ => None,
+        }
+    }
+}
+
+/// Restrictions attached to a protected branch pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BranchRules {
+    pub allow_direct_commits: bool,
+    pub allow_force_push: bool,
+    pub require_tag_on_finish: bool,
+    pub merge_strategies: Vec<MergeStrategy>,
+}
+
+impl BranchRules {
+    /// Rules for a protected pattern that has no `[safety.rules]` entry.
+    pub fn protected() -> Self {
+        Self {
+            allow_direct_commits: false,
+            allow_force_push: false,
+            require_tag_on_finish: false,
+            merge_strategies: vec![MergeStrategy::Squash],
+        }
+    }
+
+    /// Rules for branches that match no protected pattern.
+    pub fn unprotected() -> Self {
+        Self {
+            allow_direct_commits: true,
+            allow_force_push: true,
+            require_tag_on_finish: false,
+            merge_strategies: vec![MergeStrategy::Squash],
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct RawWgitConfig {
+    /// Config format version; see `settings::CONFIG_VERSION`.
+    version: Option<i64>,
+    locked: Option<Vec<String>>,
     safety: Option<RawSafetyConfig>,
+    sync: Option<RawSyncConfig>,
+    prune: Option<RawPruneConfig>,
+    branch: Option<RawBranchConfig>,
+    issue: Option<RawIssueConfig>,
+    commit: Option<RawCommitConfig>,
+    github: Option<RawGithubConfig>,
+    backport: Option<RawBackportConfig>,
+    flow: Option<RawFlowConfig>,
+    release: Option<RawReleaseConfig>,
+    update: Option<RawUpdateConfig>,
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct RawSafetyConfig {
     protected_branches: Option<Vec<String>>,
     auto_bootstrap_commit_on_start: Option<bool>,
     require_double_confirm_for_hard_reset: Option<bool>,
+    rules: Option<BTreeMap<String, RawBranchRules>>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBranchRules {
+    allow_direct_commits: Option<bool>,
+    allow_force_push: Option<bool>,
+    require_tag_on_finish: Option<bool>,
+    merge_strategies: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawSyncConfig {
+    refresh_strategy: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawPruneConfig {
+    stale_days: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBranchConfig {
+    types: Option<Vec<String>>,
+    slug_pattern: Option<String>,
+    max_length: Option<usize>,
+    require_issue_key: Option<bool>,
+    issue_key_pattern: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawIssueConfig {
+    placement: Option<String>,
+    trailer_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawGithubConfig {
+    api_base: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawBackportConfig {
+    targets: Option<Vec<String>>,
+    branch_prefix: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawFlowConfig {
+    model: Option<String>,
+    primary_branch: Option<String>,
+    develop_branch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawReleaseConfig {
+    version_files: Option<Vec<String>>,
+    assets: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawUpdateConfig {
+    repo: Option<String>,
+    base_url: Option<String>,
+    channel: Option<String>,
+    asset_template: Option<String>,
+    check: Option<bool>,
+    check_interval_hours: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawCommitConfig {
+    require_signoff: Option<bool>,
+    trailers: Option<Vec<String>>,
 }
 
 impl Default for WgitConfig {
     fn default() -> Self {
         Self {
-            protected_branches: vec!["main".to_string()],
+            protected_branches: vec!["main".to_string(), "master".to_string()],
+            branch_rules: ["main", "master"]
+                .into_iter()
+                .map(|branch| {
+                    let rules = BranchRules {
+                        require_tag_on_finish: true,
+                        ..BranchRules::protected()
+                    };
+                    (branch.to_string(), rules)
+                })
+                .collect(),
             auto_bootstrap_commit_on_start: false,
             require_double_confirm_for_hard_reset: true,
+            refresh_strategy: RefreshStrategy::Rebase,
+            stale_branch_days: 90,
+            branch_naming: BranchNaming::default(),
+            issue: IssueConfig::default(),
+            commit: CommitConfig::default(),
+            github_api_base: "https://api.github.com".to_string(),
+            backport: BackportConfig::default(),
+            flow: FlowConfig::default(),
+            release: ReleaseConfig::default(),
+            update: UpdateConfig::default(),
         }
     }
 }
@@ -36,17 +490,457 @@ pub fn config_path(repo_root: &Path) -> PathBuf {
     repo_root.join(".git").join("wgit.toml")
 }
 
+/// Committed team policy, shared by everyone who clones the repository.
+pub fn team_config_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(".wgit.toml")
+}
+
+/// `$XDG_CONFIG_HOME/wgit/config.toml`, else `~/.config/wgit/config.toml`
+/// (`%APPDATA%\wgit\config.toml` on Windows).
+pub fn global_config_path() -> Option<PathBuf> {
+    let from_env = |key: &str| {
+        std::env::var_os(key)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+    };
+    let base = from_env("XDG_CONFIG_HOME").or_else(|| {
+        if cfg!(target_os = "windows") {
+            from_env("APPDATA")
+        } else {
+            from_env("HOME").map(|home| home.join(".config"))
+        }
+    })?;
+    Some(base.join("wgit").join("config.toml"))
+}
+
+/// Built-in defaults, global, team and local config merged key by key.
 pub fn load_config(repo_root: &Path) -> Result<WgitConfig> {
-    let path = config_path(repo_root);
-    if !path.exists() {
-        return Ok(WgitConfig::default());
+    load_layers(repo_root)?.resolve()
+}
+
+/// Built-in defaults and the global config only, for work that must not be
+/// steered by whichever repository wgit happens to run in.
+pub fn load_global_config() -> Result<WgitConfig> {
+    let files = global_config_path()
+        .map(|path| (ConfigLayer::Global, path))
+        .into_iter()
+        .collect();
+    load_files(files, team_config_path(Path::new(".")))?.resolve()
+}
+
+/// Config sources in precedence order; later layers override earlier ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigLayer {
+    Default,
+    /// Personal preferences for every repository.
+    Global,
+    /// `.wgit.toml`, committed with the repository.
+    Team,
+    /// `.git/wgit.toml`, this clone only.
+    Local,
+}
+
+impl ConfigLayer {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Global => "global",
+            Self::Team => "team",
+            Self::Local => "local",
+        }
+    }
+}
+
+/// Where an effective value was set; `path` is `None` for built-in defaults.
+#[derive(Clone, Debug)]
+pub struct ConfigOrigin {
+    pub layer: ConfigLayer,
+    pub path: Option<PathBuf>,
+}
+
+impl ConfigOrigin {
+    pub fn describe(&self) -> String {
+        match &self.path {
+            Some(path) => path.display().to_string(),
+            None => "built-in defaults".to_string(),
+        }
+    }
+}
+
+/// One effective value as shown by `wgit config show`.
+#[derive(Clone, Debug)]
+pub struct ConfigEntry {
+    pub parts: Vec<String>,
+    /// Dotted key, e.g. `safety.rules."release/*".allow_force_push`.
+    pub key: String,
+    pub value: toml::Value,
+    pub origin: ConfigOrigin,
+    pub locked: bool,
+}
+
+/// Top-level team config key listing keys later layers may not change.
+pub const LOCKED_KEY: &str = "locked";
+/// Top-level key holding the config format version.
+pub const VERSION_KEY: &str = "version";
+
+/// Sections the committed team file may neither set nor lock: where wgit
+/// downloads its own updates from, and which host receives `GITHUB_TOKEN`,
+/// are the user's choice, not the project's.
+const PERSONAL_SECTIONS: [&str; 2] = ["update", "github"];
+
+/// Whether `key` is in a section only global and local config may set.
+pub fn is_personal(key: &[String]) -> bool {
+    key.first()
+        .is_some_and(|section| PERSONAL_SECTIONS.contains(&section.as_str()))
+}
+
+/// All config layers merged leaf by leaf: tables merge, any other value
+/// (arrays included) replaces the one below it.
+#[derive(Debug)]
+pub struct LayeredConfig {
+    merged: toml::Table,
+    origins: BTreeMap<Vec<String>, ConfigOrigin>,
+    /// Keys, or whole tables, that `.wgit.toml` marks as non-overridable.
+    locked: Vec<Vec<String>>,
+    team_path: PathBuf,
+}
+
+pub fn load_layers(repo_root: &Path) -> Result<LayeredConfig> {
+    let team_path = team_config_path(repo_root);
+    let mut files = Vec::new();
+    if let Some(path) = global_config_path() {
+        files.push((ConfigLayer::Global, path));
     }
+    files.push((ConfigLayer::Team, team_path.clone()));
+    files.push((ConfigLayer::Local, config_path(repo_root)));
+    load_files(files, team_path)
+}
 
-    let content = fs::read_to_string(&path)
+fn load_files(files: Vec<(ConfigLayer, PathBuf)>, team_path: PathBuf) -> Result<LayeredConfig> {
+    let defaults = default_table();
+    let mut layers = LayeredConfig {
+        merged: toml::Table::new(),
+        origins: BTreeMap::new(),
+        locked: Vec::new(),
+        team_path,
+    };
+    let mut tables = Vec::new();
+    for (layer, path) in files {
+        let Some((table, locked)) = read_layer(layer, &path)? else {
+            continue;
+        };
+        if layer == ConfigLayer::Team
+            && let Some(section) = PERSONAL_SECTIONS
+                .iter()
+                .find(|section| table.contains_key(**section))
+        {
+            bail!(
+                "`[{section}]` cannot be set in the team config {}; it only applies from the global or local config",
+                path.display()
+            );
+        }
+        if let Some(locked) = locked {
+            if layer != ConfigLayer::Team {
+                bail!(
+                    "`{LOCKED_KEY}` is only read from the team config {}; remove it from {}",
+                    layers.team_path.display(),
+                    path.display()
+                );
+            }
+            layers.locked = parse_locked(locked, &path, &defaults)?;
+        }
+        tables.push((layer, path, table));
+    }
+
+    layers.apply(ConfigLayer::Default, None, defaults)?;
+    for (layer, path, table) in tables {
+        layers.apply(layer, Some(path), table)?;
+    }
+    Ok(layers)
+}
+
+/// Built-in defaults as a TOML table.
+pub fn default_table() -> toml::Table {
+    toml::from_str(DEFAULT_CONFIG).expect("built-in default config is valid TOML")
+}
+
+/// Reads one config file, migrating an older local file in place first.
+/// Returns its values without `version` and `locked`, plus the `locked` list.
+fn read_layer(
+    layer: ConfigLayer,
+    path: &Path,
+) -> Result<Option<(toml::Table, Option<Vec<String>>)>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut content = fs::read_to_string(path)
         .with_context(|| format!("failed to read config file: {}", path.display()))?;
-    let raw: RawWgitConfig = toml::from_str(&content)
+    // Unknown keys and wrong types are reported here, with line and column in this file.
+    // An invalid file is never migrated, so its backup and rewrite wait until it is fixed.
+    let parse = |content: &str| {
+        toml::from_str::<RawWgitConfig>(content)
+            .with_context(|| format!("failed to parse config file: {}", path.display()))
+    };
+    let mut raw = parse(&content)?;
+    if let Some(migrated) = settings::migrate(layer, &content)
+        .with_context(|| format!("failed to migrate config file: {}", path.display()))?
+    {
+        let backup = unused_backup_path(path, migrated.from);
+        fs::write(&backup, &content)
+            .with_context(|| format!("failed to back up config file: {}", backup.display()))?;
+        fs::write(path, &migrated.content)
+            .with_context(|| format!("failed to write config file: {}", path.display()))?;
+        eprintln!(
+            "Migrated {} from config version {} to {} (previous file kept at {}):",
+            path.display(),
+            migrated.from,
+            settings::CONFIG_VERSION,
+            backup.display()
+        );
+        for note in &migrated.notes {
+            eprintln!("  - {note}");
+        }
+        content = migrated.content;
+        raw = parse(&content)?;
+    }
+    if let Some(version) = raw.version
+        && !(1..=settings::CONFIG_VERSION).contains(&version)
+    {
+        bail!(
+            "{} has config version {version}, but this wgit supports up to {}. update wgit with `wgit update`",
+            path.display(),
+            settings::CONFIG_VERSION
+        );
+    }
+    let mut table = toml::from_str::<toml::Table>(&content)
         .with_context(|| format!("failed to parse config file: {}", path.display()))?;
+    table.remove(VERSION_KEY);
+    table.remove(LOCKED_KEY);
+    Ok(Some((table, raw.locked)))
+}
+
+/// `wgit.toml.v<from>.bak`, or `wgit.toml.v<from>.<n>.bak` when an earlier
+/// migration already left that name behind.
+fn unused_backup_path(path: &Path, from: i64) -> PathBuf {
+    let mut backup = path.with_extension(format!("toml.v{from}.bak"));
+    let mut n = 1;
+    while backup.exists() {
+        backup = path.with_extension(format!("toml.v{from}.{n}.bak"));
+        n += 1;
+    }
+    backup
+}
+
+fn parse_locked(
+    entries: Vec<String>,
+    path: &Path,
+    defaults: &toml::Table,
+) -> Result<Vec<Vec<String>>> {
+    let mut locked = Vec::new();
+    for text in entries {
+        let key = split_key(&text)
+            .with_context(|| format!("invalid `{LOCKED_KEY}` entry in {}", path.display()))?;
+        if !defaults.contains_key(&key[0]) {
+            bail!(
+                "invalid `{LOCKED_KEY}` entry `{text}` in {}. unknown section `{}`; expected one of {}",
+                path.display(),
+                key[0],
+                defaults.keys().cloned().collect::<Vec<_>>().join(", ")
+            );
+        }
+        if is_personal(&key) {
+            bail!(
+                "invalid `{LOCKED_KEY}` entry `{text}` in {}. `[{}]` is personal and cannot be locked",
+                path.display(),
+                key[0]
+            );
+        }
+        locked.push(key);
+    }
+    Ok(locked)
+}
+
+impl LayeredConfig {
+    /// Validates the merged values into the config commands use.
+    pub fn resolve(&self) -> Result<WgitConfig> {
+        let raw: RawWgitConfig = toml::Value::Table(self.merged.clone())
+            .try_into()
+            .context("failed to read merged config")?;
+        build_config(raw, self)
+    }
+
+    /// Every effective value with the file it came from, sorted by key.
+    pub fn entries(&self) -> Vec<ConfigEntry> {
+        self.origins
+            .iter()
+            .filter_map(|(key, origin)| {
+                Some(ConfigEntry {
+                    parts: key.clone(),
+                    key: format_key(key),
+                    value: self.get(key)?.clone(),
+                    origin: origin.clone(),
+                    locked: self.is_locked(key),
+                })
+            })
+            .collect()
+    }
+
+    fn apply(
+        &mut self,
+        layer: ConfigLayer,
+        path: Option<PathBuf>,
+        table: toml::Table,
+    ) -> Result<()> {
+        let origin = ConfigOrigin { layer, path };
+        let mut leaves = Vec::new();
+        collect_leaves(table, &mut Vec::new(), &mut leaves);
+        for (key, value) in leaves {
+            if self.is_locked(&key) {
+                match layer {
+                    // The team value wins over personal preferences without complaint.
+                    ConfigLayer::Global => continue,
+                    ConfigLayer::Local if self.get(&key) != Some(&value) => bail!(
+                        "`{}` is locked by the team config {}; remove it from {}",
+                        format_key(&key),
+                        self.team_path.display(),
+                        origin.describe()
+                    ),
+                    // Repeating the locked value changes nothing; keep the team origin.
+                    ConfigLayer::Local => continue,
+                    ConfigLayer::Default | ConfigLayer::Team => {}
+                }
+            }
+            self.set(key, value, origin.clone());
+        }
+        Ok(())
+    }
+
+    fn get(&self, key: &[String]) -> Option<&toml::Value> {
+        let (last, parents) = key.split_last()?;
+        let mut table = &self.merged;
+        for part in parents {
+            table = table.get(part)?.as_table()?;
+        }
+        table.get(last)
+    }
+
+    fn set(&mut self, key: Vec<String>, value: toml::Value, origin: ConfigOrigin) {
+        let Some((last, parents)) = key.split_last() else {
+            return;
+        };
+        let mut table = &mut self.merged;
+        for part in parents {
+            let entry = table
+                .entry(part.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+            if !entry.is_table() {
+                *entry = toml::Value::Table(toml::Table::new());
+            }
+            table = entry.as_table_mut().expect("entry was just made a table");
+        }
+        // An empty table (`[safety.rules."x"]`) only declares the key.
+        if value.as_table().is_some_and(toml::Table::is_empty)
+            && table.get(last).is_some_and(toml::Value::is_table)
+        {
+            return;
+        }
+        table.insert(last.clone(), value);
+        self.origins
+            .retain(|existing, _| !existing.starts_with(&key) && !key.starts_with(existing));
+        self.origins.insert(key, origin);
+    }
+
+    fn is_locked(&self, key: &[String]) -> bool {
+        self.locked.iter().any(|locked| key.starts_with(locked))
+    }
+
+    /// File that set `key`, or the latest file that set anything under it.
+    fn file(&self, key: &[&str]) -> String {
+        let overlaps = |existing: &[String]| {
+            existing
+                .iter()
+                .zip(key)
+                .all(|(existing, part)| existing == part)
+        };
+        self.origins
+            .iter()
+            .filter(|(existing, _)| overlaps(existing))
+            .map(|(_, origin)| origin)
+            .max_by_key(|origin| origin.layer)
+            .map_or_else(|| "built-in defaults".to_string(), ConfigOrigin::describe)
+    }
+}
+
+/// Flattens a table into `(key, value)` leaves; arrays and empty tables are leaves.
+pub fn collect_leaves(
+    table: toml::Table,
+    prefix: &mut Vec<String>,
+    leaves: &mut Vec<(Vec<String>, toml::Value)>,
+) {
+    for (name, value) in table {
+        prefix.push(name);
+        match value {
+            toml::Value::Table(child) if !child.is_empty() => collect_leaves(child, prefix, leaves),
+            value => leaves.push((prefix.clone(), value)),
+        }
+        prefix.pop();
+    }
+}
+
+/// Splits a dotted key such as `safety.rules."release/*".allow_force_push`.
+pub fn split_key(key: &str) -> Result<Vec<String>> {
+    let invalid = || {
+        anyhow::anyhow!(
+            "invalid key `{key}`. expected dotted names such as `commit.require_signoff`; quote segments with other characters: `safety.rules.\"release/*\"`"
+        )
+    };
+    let mut parts = Vec::new();
+    let mut rest = key.trim();
+    loop {
+        let (part, tail) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').ok_or_else(invalid)?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => {
+                let end = rest.find('.').unwrap_or(rest.len());
+                let part = &rest[..end];
+                if !part.chars().all(is_bare_key_char) {
+                    return Err(invalid());
+                }
+                (part, &rest[end..])
+            }
+        };
+        if part.is_empty() {
+            return Err(invalid());
+        }
+        parts.push(part.to_string());
+        if tail.is_empty() {
+            return Ok(parts);
+        }
+        rest = tail.strip_prefix('.').ok_or_else(invalid)?;
+    }
+}
+
+pub fn format_key(parts: &[String]) -> String {
+    parts
+        .iter()
+        .map(|part| {
+            if !part.is_empty() && part.chars().all(is_bare_key_char) {
+                part.clone()
+            } else {
+                format!("\"{part}\"")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
 
+fn is_bare_key_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_' || ch == '-'
+}
+
+fn build_config(raw: RawWgitConfig, layers: &LayeredConfig) -> Result<WgitConfig> {
     let safety = raw.safety;
     let mut protected_branches = safety
         .as_ref()
@@ -61,6 +955,53 @@ pub fn load_config(repo_root: &Path) -> Result<WgitConfig> {
         protected_branches = WgitConfig::default().protected_branches;
     }
 
+    let mut branch_rules = WgitConfig::default().branch_rules;
+    let raw_rules = safety
+        .as_ref()
+        .and_then(|value| value.rules.as_ref())
+        .cloned()
+        .unwrap_or_default();
+    for (pattern, raw_rule) in raw_rules {
+        let base = branch_rules
+            .remove(pattern.trim())
+            .unwrap_or_else(BranchRules::protected);
+        let merge_strategies = match raw_rule.merge_strategies {
+            Some(values) => {
+                let mut strategies = Vec::new();
+                for value in values {
+                    let Some(strategy) = MergeStrategy::parse(&value) else {
+                        bail!(
+                            "invalid merge strategy `{value}` for safety.rules.\"{pattern}\" in {}. expected `squash` or `merge`",
+                            layers.file(&["safety", "rules", &pattern, "merge_strategies"])
+                        );
+                    };
+                    if !strategies.contains(&strategy) {
+                        strategies.push(strategy);
+                    }
+                }
+                if strategies.is_empty() {
+                    bail!(
+                        "safety.rules.\"{pattern}\".merge_strategies cannot be empty in {}",
+                        layers.file(&["safety", "rules", &pattern, "merge_strategies"])
+                    );
+                }
+                strategies
+            }
+            None => base.merge_strategies.clone(),
+        };
+        let rule = BranchRules {
+            allow_direct_commits: raw_rule
+                .allow_direct_commits
+                .unwrap_or(base.allow_direct_commits),
+            allow_force_push: raw_rule.allow_force_push.unwrap_or(base.allow_force_push),
+            require_tag_on_finish: raw_rule
+                .require_tag_on_finish
+                .unwrap_or(base.require_tag_on_finish),
+            merge_strategies,
+        };
+        branch_rules.insert(pattern.trim().to_string(), rule);
+    }
+
     let auto_bootstrap_commit_on_start = safety
         .as_ref()
         .and_then(|value| value.auto_bootstrap_commit_on_start)
@@ -70,13 +1011,423 @@ pub fn load_config(repo_root: &Path) -> Result<WgitConfig> {
         .and_then(|value| value.require_double_confirm_for_hard_reset)
         .unwrap_or(WgitConfig::default().require_double_confirm_for_hard_reset);
 
+    let refresh_strategy = match raw.sync.and_then(|value| value.refresh_strategy) {
+        Some(value) => match RefreshStrategy::parse(&value) {
+            Some(strategy) => strategy,
+            None => bail!(
+                "invalid sync.refresh_strategy `{value}` in {}. expected `rebase` or `merge`",
+                layers.file(&["sync", "refresh_strategy"])
+            ),
+        },
+        None => WgitConfig::default().refresh_strategy,
+    };
+    let stale_branch_days = raw
+        .prune
+        .and_then(|value| value.stale_days)
+        .filter(|days| *days > 0)
+        .unwrap_or(WgitConfig::default().stale_branch_days);
+    let branch_naming = match raw.branch {
+        Some(raw_branch) => load_branch_naming(raw_branch, layers)?,
+        None => BranchNaming::default(),
+    };
+    let issue = match raw.issue {
+        Some(raw_issue) => load_issue_config(raw_issue, layers)?,
+        None => IssueConfig::default(),
+    };
+    let commit = match raw.commit {
+        Some(raw_commit) => load_commit_config(raw_commit, layers)?,
+        None => CommitConfig::default(),
+    };
+    let github_api_base = raw
+        .github
+        .and_then(|value| value.api_base)
+        .map(|value| value.trim().trim_end_matches('/').to_string())
+        .filter(|value| !value.is_empty())
+        .unwrap_or(WgitConfig::default().github_api_base);
+    let backport = match raw.backport {
+        Some(raw_backport) => load_backport_config(raw_backport, layers)?,
+        None => BackportConfig::default(),
+    };
+    let flow = match raw.flow {
+        Some(raw_flow) => load_flow_config(raw_flow, layers)?,
+        None => FlowConfig::default(),
+    };
+    let release = match raw.release {
+        Some(raw_release) => load_release_config(raw_release, layers)?,
+        None => ReleaseConfig::default(),
+    };
+    let update = match raw.update {
+        Some(raw_update) => load_update_config(raw_update, layers)?,
+        None => UpdateConfig::default(),
+    };
+
     Ok(WgitConfig {
         protected_branches,
+        branch_rules,
         auto_bootstrap_commit_on_start,
         require_double_confirm_for_hard_reset,
+        refresh_strategy,
+        stale_branch_days,
+        branch_naming,
+        issue,
+        commit,
+        github_api_base,
+        backport,
+        flow,
+        release,
+        update,
+    })
+}
+
+fn load_update_config(raw: RawUpdateConfig, layers: &LayeredConfig) -> Result<UpdateConfig> {
+    let repo = raw
+        .repo
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(repo) = &repo
+        && !is_repo_slug(repo)
+    {
+        bail!(
+            "invalid update.repo `{repo}` in {}. expected `owner/name`",
+            layers.file(&["update", "repo"])
+        );
+    }
+    let base_url = raw
+        .base_url
+        .map(|value| value.trim().trim_end_matches('/').to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(base_url) = &base_url
+        && !base_url.starts_with("https://")
+        && !base_url.starts_with("http://")
+    {
+        bail!(
+            "invalid update.base_url `{base_url}` in {}. expected an http(s) URL",
+            layers.file(&["update", "base_url"])
+        );
+    }
+    let channel = match raw.channel {
+        Some(value) => match UpdateChannel::parse(&value) {
+            Some(channel) => channel,
+            None => bail!(
+                "invalid update.channel `{value}` in {}. expected `stable` or `prerelease`",
+                layers.file(&["update", "channel"])
+            ),
+        },
+        None => UpdateChannel::default(),
+    };
+    let asset_template = raw
+        .asset_template
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    if let Some(template) = &asset_template {
+        let unknown: Vec<&str> = template
+            .split('{')
+            .skip(1)
+            .map(|part| part.split('}').next().unwrap_or_default())
+            .filter(|name| !ASSET_PLACEHOLDERS.contains(name))
+            .collect();
+        if !unknown.is_empty() || template.contains('/') || template.contains('\\') {
+            bail!(
+                "invalid update.asset_template `{template}` in {}. expected a file name using {}",
+                layers.file(&["update", "asset_template"]),
+                ASSET_PLACEHOLDERS
+                    .iter()
+                    .map(|name| format!("{{{name}}}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+    let defaults = UpdateConfig::default();
+    let check_interval_hours = raw
+        .check_interval_hours
+        .unwrap_or(defaults.check_interval_hours);
+    if check_interval_hours == 0 {
+        bail!(
+            "invalid update.check_interval_hours `0` in {}. expected at least 1; set update.check = false to turn the check off",
+            layers.file(&["update", "check_interval_hours"])
+        );
+    }
+    Ok(UpdateConfig {
+        repo,
+        base_url,
+        channel,
+        asset_template,
+        check: raw.check.unwrap_or(defaults.check),
+        check_interval_hours,
+    })
+}
+
+/// `owner/name` with no empty or extra segments.
+pub fn is_repo_slug(value: &str) -> bool {
+    let mut parts = value.split('/');
+    let valid = |part: Option<&str>| {
+        part.is_some_and(|part| !part.is_empty() && !part.contains(char::is_whitespace))
+    };
+    valid(parts.next()) && valid(parts.next()) && parts.next().is_none()
+}
+
+fn load_release_config(raw: RawReleaseConfig, layers: &LayeredConfig) -> Result<ReleaseConfig> {
+    let mut version_files = Vec::new();
+    for entry in raw.version_files.unwrap_or_default() {
+        let file = entry.trim();
+        if file.is_empty() {
+            continue;
+        }
+        if Path::new(file).is_absolute() || file.split(['/', '\\']).any(|part| part == "..") {
+            bail!(
+                "invalid release.version_files entry `{file}` in {}. use a path inside the repository, e.g. `Cargo.toml`",
+                layers.file(&["release", "version_files"])
+            );
+        }
+        let Some(format) = VersionFormat::detect(file) else {
+            bail!(
+                "unsupported release.version_files entry `{file}` in {}. supported: Cargo.toml, package.json, pyproject.toml, VERSION, *.txt",
+                layers.file(&["release", "version_files"])
+            );
+        };
+        version_files.push(VersionFile {
+            path: file.to_string(),
+            format,
+        });
+    }
+    let assets = raw
+        .assets
+        .unwrap_or_default()
+        .into_iter()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect();
+    Ok(ReleaseConfig {
+        version_files,
+        assets,
+    })
+}
+
+fn load_flow_config(raw: RawFlowConfig, layers: &LayeredConfig) -> Result<FlowConfig> {
+    let defaults = FlowConfig::default();
+    let model = match raw.model {
+        Some(value) => match FlowModel::parse(&value) {
+            Some(model) => model,
+            None => bail!(
+                "invalid flow.model `{value}` in {}. expected `github` or `gitflow`",
+                layers.file(&["flow", "model"])
+            ),
+        },
+        None => defaults.model,
+    };
+    let branch = |key: &str, value: Option<String>| -> Result<Option<String>> {
+        match value.map(|value| value.trim().to_string()) {
+            Some(name) if name.is_empty() || name.contains(char::is_whitespace) => bail!(
+                "invalid flow.{key} `{name}` in {}. use a branch name such as `main`",
+                layers.file(&["flow", key])
+            ),
+            other => Ok(other),
+        }
+    };
+    let primary_branch = branch("primary_branch", raw.primary_branch)?;
+    let develop_branch =
+        branch("develop_branch", raw.develop_branch)?.unwrap_or(defaults.develop_branch);
+    if primary_branch.as_deref() == Some(develop_branch.as_str()) {
+        bail!(
+            "flow.primary_branch and flow.develop_branch must differ in {}",
+            layers.file(&["flow", "primary_branch"])
+        );
+    }
+    Ok(FlowConfig {
+        model,
+        primary_branch,
+        develop_branch,
+    })
+}
+
+fn load_backport_config(raw: RawBackportConfig, layers: &LayeredConfig) -> Result<BackportConfig> {
+    let defaults = BackportConfig::default();
+    let targets = raw
+        .targets
+        .map(|values| {
+            values
+                .into_iter()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect()
+        })
+        .unwrap_or(defaults.targets);
+    let branch_prefix = match raw
+        .branch_prefix
+        .map(|value| value.trim().trim_matches('/').to_string())
+    {
+        Some(prefix) if prefix.is_empty() || prefix.contains(char::is_whitespace) => bail!(
+            "invalid backport.branch_prefix `{prefix}` in {}. example: `backport`",
+            layers.file(&["backport", "branch_prefix"])
+        ),
+        Some(prefix) => prefix,
+        None => defaults.branch_prefix,
+    };
+    Ok(BackportConfig {
+        targets,
+        branch_prefix,
+    })
+}
+
+/// Entries are `"Key"` or `"Key: default value"`.
+fn load_commit_config(raw: RawCommitConfig, layers: &LayeredConfig) -> Result<CommitConfig> {
+    let mut trailers = Vec::new();
+    for entry in raw.trailers.unwrap_or_default() {
+        let (key, value) = entry.split_once(':').unwrap_or((entry.as_str(), ""));
+        let key = key.trim();
+        if key.is_empty() || key.contains(char::is_whitespace) {
+            bail!(
+                "invalid commit.trailers entry `{entry}` in {}. expected `Key` or `Key: value`, e.g. `Reviewed-by`",
+                layers.file(&["commit", "trailers"])
+            );
+        }
+        trailers.push((key.to_string(), value.trim().to_string()));
+    }
+    Ok(CommitConfig {
+        require_signoff: raw.require_signoff.unwrap_or(false),
+        trailers,
+    })
+}
+
+fn load_issue_config(raw: RawIssueConfig, layers: &LayeredConfig) -> Result<IssueConfig> {
+    let defaults = IssueConfig::default();
+    let placement = match raw.placement {
+        Some(value) => match IssuePlacement::parse(&value) {
+            Some(placement) => placement,
+            None => bail!(
+                "invalid issue.placement `{value}` in {}. expected `trailer`, `scope` or `none`",
+                layers.file(&["issue", "placement"])
+            ),
+        },
+        None => defaults.placement,
+    };
+    let trailer_key = match raw.trailer_key.map(|value| value.trim().to_string()) {
+        Some(key) if key.is_empty() || key.contains(char::is_whitespace) || key.contains(':') => {
+            bail!(
+                "invalid issue.trailer_key `{key}` in {}. use a single word such as `Refs`",
+                layers.file(&["issue", "trailer_key"])
+            )
+        }
+        Some(key) => key,
+        None => defaults.trailer_key,
+    };
+
+    Ok(IssueConfig {
+        placement,
+        trailer_key,
     })
 }
 
+fn load_branch_naming(raw: RawBranchConfig, layers: &LayeredConfig) -> Result<BranchNaming> {
+    let defaults = BranchNaming::default();
+    let types = match raw.types {
+        Some(values) => {
+            let types: Vec<String> = values
+                .into_iter()
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+                .collect();
+            if types.is_empty() {
+                bail!(
+                    "branch.types cannot be empty in {}",
+                    layers.file(&["branch", "types"])
+                );
+            }
+            types
+        }
+        None => defaults.types,
+    };
+    let compile = |key: &str, pattern: Option<String>, fallback: Regex| -> Result<Regex> {
+        match pattern {
+            Some(pattern) => Regex::new(&pattern).with_context(|| {
+                format!(
+                    "invalid regex for branch.{key} in {}",
+                    layers.file(&["branch", key])
+                )
+            }),
+            None => Ok(fallback),
+        }
+    };
+    let slug_pattern = compile("slug_pattern", raw.slug_pattern, defaults.slug_pattern)?;
+    let issue_key_pattern = compile(
+        "issue_key_pattern",
+        raw.issue_key_pattern,
+        defaults.issue_key_pattern,
+    )?;
+
+    Ok(BranchNaming {
+        types,
+        slug_pattern,
+        max_length: raw
+            .max_length
+            .filter(|value| *value > 0)
+            .unwrap_or(defaults.max_length),
+        require_issue_key: raw.require_issue_key.unwrap_or(defaults.require_issue_key),
+        issue_key_pattern,
+    })
+}
+
+/// Built-in defaults: the lowest config layer, and the commented template
+/// `wgit init` writes to `.git/wgit.toml`.
+const DEFAULT_CONFIG: &str = r#"[safety]
+protected_branches = ["main", "master"]
+auto_bootstrap_commit_on_start = false
+require_double_confirm_for_hard_reset = true
+
+[safety.rules.main]
+allow_direct_commits = false
+allow_force_push = false
+require_tag_on_finish = true
+merge_strategies = ["squash"]
+
+[safety.rules.master]
+allow_direct_commits = false
+allow_force_push = false
+require_tag_on_finish = true
+merge_strategies = ["squash"]
+
+[sync]
+refresh_strategy = "rebase"
+
+[prune]
+stale_days = 90
+
+[branch]
+types = ["feature", "bugfix", "hotfix", "release"]
+slug_pattern = "^[a-z0-9._-]+$"
+max_length = 64
+require_issue_key = false
+
+[issue]
+placement = "trailer"
+trailer_key = "Refs"
+
+[commit]
+require_signoff = false
+trailers = []
+
+[github]
+api_base = "https://api.github.com"
+
+[backport]
+targets = ["main", "release/*"]
+branch_prefix = "backport"
+
+[flow]
+model = "github"
+develop_branch = "develop"
+
+[release]
+version_files = []
+assets = []
+
+[update]
+channel = "stable"
+check = true
+check_interval_hours = 24
+"#;
+
 pub fn ensure_default_config(repo_root: &Path) -> Result<WgitConfig> {
     let path = config_path(repo_root);
 
@@ -84,18 +1435,71 @@ pub fn ensure_default_config(repo_root: &Path) -> Result<WgitConfig> {
         return load_config(repo_root);
     }
 
-    let content = "[safety]\nprotected_branches = [\"main\"]\nauto_bootstrap_commit_on_start = false\nrequire_double_confirm_for_hard_reset = true\n";
+    // Everything stays commented out so `.wgit.toml` and the global config apply.
+    let mut content = format!(
+        "# Local overrides for this clone. They win over .wgit.toml (team) and\n# ~/.config/wgit/config.toml (global); `wgit config show --origin` lists\n# every effective value. Change settings with `wgit config` or uncomment them here.\n{VERSION_KEY} = {}\n\n",
+        settings::CONFIG_VERSION
+    );
+    for line in DEFAULT_CONFIG.lines() {
+        if line.is_empty() {
+            content.push('\n');
+        } else {
+            content.push_str(&format!("# {line}\n"));
+        }
+    }
     fs::write(&path, content)
         .with_context(|| format!("failed to write config file: {}", path.display()))?;
 
     load_config(repo_root)
 }
 
-pub fn is_protected_branch(repo_root: &Path, branch: &str) -> Result<bool> {
-    let branch = branch.trim();
-    let cfg = load_config(repo_root)?;
-    Ok(cfg
-        .protected_branches
-        .iter()
-        .any(|protected| protected.trim() == branch))
+#[cfg(test)]
+mod tests {
+    use super::{format_key, split_key};
+
+    #[test]
+    fn dotted_keys_split_on_dots_outside_quotes() {
+        assert_eq!(
+            split_key("commit.require_signoff").unwrap(),
+            vec!["commit", "require_signoff"]
+        );
+        assert_eq!(
+            split_key(" safety.rules.\"release/*\".allow_force_push ").unwrap(),
+            vec!["safety", "rules", "release/*", "allow_force_push"]
+        );
+        assert_eq!(
+            split_key("safety.rules.\"v1.x\"").unwrap(),
+            vec!["safety", "rules", "v1.x"]
+        );
+    }
+
+    #[test]
+    fn malformed_keys_are_rejected() {
+        for key in [
+            "",
+            "commit.",
+            ".commit",
+            "commit..trailers",
+            "safety.rules.release/*",
+            "safety.rules.\"main",
+            "safety.rules.\"\"",
+            "safety.\"rules\"x",
+        ] {
+            assert!(split_key(key).is_err(), "accepted `{key}`");
+        }
+    }
+
+    #[test]
+    fn formatted_keys_quote_only_when_needed_and_round_trip() {
+        let parts: Vec<String> = ["safety", "rules", "release/*", "merge_strategies"]
+            .map(String::from)
+            .to_vec();
+        let text = format_key(&parts);
+        assert_eq!(text, "safety.rules.\"release/*\".merge_strategies");
+        assert_eq!(split_key(&text).unwrap(), parts);
+        assert_eq!(
+            format_key(&["update".to_string(), "base-url".to_string()]),
+            "update.base-url"
+        );
+    }
 }
@@ -0,0 +1,228 @@
+use crate::config::BranchNaming;
+use crate::utils::CommitDraft;
+use anyhow::{Result, bail};
+use regex::{Regex, RegexBuilder};
+
+/// Turns free text into a branch slug: "Login Form" becomes "login-form".
+pub fn slugify(input: &str) -> String {
+    let mut slug = String::new();
+    let mut pending_dash = false;
+    for ch in input.trim().chars() {
+        let ch = ch.to_ascii_lowercase();
+        if ch.is_ascii_alphanumeric() || ch == '.' || ch == '_' {
+            if pending_dash && !slug.is_empty() {
+                slug.push('-');
+            }
+            pending_dash = false;
+            slug.push(ch);
+        } else {
+            pending_dash = true;
+        }
+    }
+    while slug.contains("..") {
+        slug = slug.replace("..", ".");
+    }
+    slug.trim_matches(|ch| ch == '.' || ch == '-').to_string()
+}
+
+/// Builds `<type>/<slug>` and checks it against the `[branch]` rules.
+/// Errors name the rule that failed and show a valid example.
+pub fn build_branch_name(naming: &BranchNaming, branch_type: &str, slug: &str) -> Result<String> {
+    let full = format!("{branch_type}/{slug}");
+    let example = if naming.require_issue_key {
+        format!("{branch_type}/proj-123-login-form")
+    } else {
+        format!("{branch_type}/login-form")
+    };
+
+    if slug.is_empty() {
+        bail!("invalid branch name `{full}`: name is empty after slugify. example: {example}");
+    }
+    if !naming.slug_pattern.is_match(slug) {
+        bail!(
+            "invalid branch name `{full}`: `{slug}` does not match branch.slug_pattern `{}`. example: {example}",
+            naming.slug_pattern.as_str()
+        );
+    }
+    if slug.contains("..") || slug.starts_with('.') || slug.ends_with('.') || slug.ends_with('/') {
+        bail!(
+            "invalid branch name `{full}`: git ref rules forbid `..` and a leading or trailing `.`. example: {example}"
+        );
+    }
+    let length = full.chars().count();
+    if length > naming.max_length {
+        bail!(
+            "invalid branch name `{full}`: {length} characters exceeds branch.max_length {}. example: {example}",
+            naming.max_length
+        );
+    }
+    if naming.require_issue_key && !contains_issue_key(naming, slug) {
+        bail!(
+            "invalid branch name `{full}`: branch.require_issue_key needs an issue key matching `{}`. example: {example}",
+            naming.issue_key_pattern.as_str()
+        );
+    }
+    Ok(full)
+}
+
+/// Slugs are lowercase, so keys such as `PROJ-123` are matched case-insensitively.
+fn contains_issue_key(naming: &BranchNaming, slug: &str) -> bool {
+    find_issue_key(naming, slug).is_some()
+}
+
+/// A ticket reference typed into `wgit start`: a tracker key such as
+/// `PROJ-123`, or a GitHub issue number written as `#42`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IssueRef {
+    pub key: String,
+    pub number: Option<u64>,
+}
+
+impl IssueRef {
+    /// Branch-name form of the reference: `proj-123` or `42`.
+    pub fn slug(&self) -> String {
+        match self.number {
+            Some(number) => number.to_string(),
+            None => self.key.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// Parses `#42` or a key matching `branch.issue_key_pattern` (any case).
+pub fn parse_issue_ref(naming: &BranchNaming, input: &str) -> Option<IssueRef> {
+    let input = input.trim();
+    if let Some(number) = input.strip_prefix('#') {
+        let number = number.parse::<u64>().ok()?;
+        return Some(IssueRef {
+            key: format!("#{number}"),
+            number: Some(number),
+        });
+    }
+    let key = find_issue_key(naming, input)?;
+    (key.len() == input.len()).then_some(IssueRef { key, number: None })
+}
+
+/// Recovers the issue key from a branch name like `feature/proj-123-login-form`.
+pub fn issue_key_from_branch(naming: &BranchNaming, branch: &str) -> Option<String> {
+    let slug = branch.split_once('/').map_or(branch, |(_, slug)| slug);
+    find_issue_key(naming, slug)
+}
+
+fn find_issue_key(naming: &BranchNaming, text: &str) -> Option<String> {
+    let pattern = RegexBuilder::new(naming.issue_key_pattern.as_str())
+        .case_insensitive(true)
+        .build()
+        .ok()?;
+    pattern
+        .find(text)
+        .map(|found| found.as_str().to_ascii_uppercase())
+}
+
+/// An existing commit message split back into the editor's fields.
+pub struct ParsedCommit {
+    /// `None` when the header is not `type(scope): subject`.
+    pub commit_type: Option<String>,
+    pub draft: CommitDraft,
+}
+
+/// Parses a conventional header and drops the trailing trailer block
+/// (`trailers` as read by git) from the body.
+pub fn parse_commit_message(message: &str, trailers: &[(String, String)]) -> ParsedCommit {
+    let message = message.trim();
+    let (header, rest) = message.split_once('\n').unwrap_or((message, ""));
+    let body = rest.trim();
+    let (before, last) = body.rsplit_once("\n\n").unwrap_or(("", body));
+    let is_trailer_block = !trailers.is_empty()
+        && last.lines().all(|line| {
+            line.split_once(':').is_some_and(|(key, _)| {
+                trailers
+                    .iter()
+                    .any(|(trailer_key, _)| trailer_key == key.trim())
+            })
+        });
+    let body = if is_trailer_block {
+        before.trim()
+    } else {
+        body
+    }
+    .to_string();
+
+    let pattern = Regex::new(r"^([a-z]+)(?:\(([^)]*)\))?: (.+)$").expect("header pattern is valid");
+    let (commit_type, scope, subject) = match pattern.captures(header.trim()) {
+        Some(captures) => (
+            Some(captures[1].to_string()),
+            captures
+                .get(2)
+                .map_or("", |value| value.as_str())
+                .to_string(),
+            captures[3].to_string(),
+        ),
+        None => (None, String::new(), header.trim().to_string()),
+    };
+    ParsedCommit {
+        commit_type,
+        draft: CommitDraft {
+            scope,
+            subject,
+            body,
+            trailers: Vec::new(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slugify_lowercases_and_joins_words() {
+        assert_eq!(slugify("Login Form"), "login-form");
+        assert_eq!(slugify("  Fix: DB..Pool!  "), "fix-db.pool");
+        assert_eq!(slugify("--Keep_under.scores--"), "keep_under.scores");
+        assert_eq!(slugify(".hidden."), "hidden");
+        assert_eq!(slugify("!!!"), "");
+    }
+
+    #[test]
+    fn build_branch_name_accepts_a_valid_slug() {
+        let naming = BranchNaming::default();
+        assert_eq!(
+            build_branch_name(&naming, "feature", "login-form").unwrap(),
+            "feature/login-form"
+        );
+    }
+
+    #[test]
+    fn build_branch_name_reports_the_failed_rule() {
+        let naming = BranchNaming::default();
+        let error = |slug: &str| {
+            build_branch_name(&naming, "feature", slug)
+                .unwrap_err()
+                .to_string()
+        };
+        assert!(error("").contains("empty after slugify"));
+        assert!(error("Login").contains("branch.slug_pattern"));
+        assert!(error("a..b").contains("git ref rules"));
+        assert!(error(&"x".repeat(60)).contains("branch.max_length 64"));
+        assert!(error("Login").contains("example: feature/login-form"));
+    }
+
+    #[test]
+    fn build_branch_name_requires_an_issue_key_when_configured() {
+        let naming = BranchNaming {
+            require_issue_key: true,
+            ..BranchNaming::default()
+        };
+        assert_eq!(
+            build_branch_name(&naming, "bugfix", "proj-123-login").unwrap(),
+            "bugfix/proj-123-login"
+        );
+        let error = build_branch_name(&naming, "bugfix", "login").unwrap_err();
+        assert!(error.to_string().contains("branch.require_issue_key"));
+        assert!(
+            error
+                .to_string()
+                .contains("example: bugfix/proj-123-login-form")
+        );
+    }
+}
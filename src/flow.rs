@@ -0,0 +1,112 @@
+use crate::config::{FlowModel, WgitConfig};
+use crate::git;
+use anyhow::{Result, bail};
+use std::path::Path;
+
+/// Branch types whose lifecycle is set by `[flow]` instead of "squash into parent".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BranchKind {
+    Release,
+    Hotfix,
+}
+
+impl BranchKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Release => "release",
+            Self::Hotfix => "hotfix",
+        }
+    }
+
+    /// Kind for a `wgit start` branch type.
+    pub fn from_type(branch_type: &str) -> Option<Self> {
+        match branch_type {
+            "release" => Some(Self::Release),
+            "hotfix" => Some(Self::Hotfix),
+            _ => None,
+        }
+    }
+}
+
+/// Kind of an existing branch, read from its `<type>/` prefix.
+pub fn branch_kind(branch: &str) -> Option<BranchKind> {
+    branch
+        .split_once('/')
+        .and_then(|(branch_type, _)| BranchKind::from_type(branch_type))
+}
+
+/// `flow.primary_branch`, else `main` or `master`.
+pub fn primary_branch(cwd: &Path, cfg: &WgitConfig) -> Result<String> {
+    let Some(primary) = &cfg.flow.primary_branch else {
+        return git::default_branch(cwd);
+    };
+    if !git::branch_exists(cwd, primary)? {
+        bail!("flow.primary_branch `{primary}` does not exist locally");
+    }
+    Ok(primary.clone())
+}
+
+/// A release or hotfix branch created by `wgit start`, which records its
+/// parent. Long-lived lines such as `release/1.x` have no record and keep
+/// the protection their pattern gives them.
+pub fn is_started_flow_branch(cwd: &Path, branch: &str) -> Result<bool> {
+    Ok(branch_kind(branch).is_some() && git::recorded_parent_branch(cwd, branch)?.is_some())
+}
+
+/// A `wgit start` release or hotfix branch that `finish` already merged or
+/// squash-merged into the primary branch. Protected-branch patterns stop
+/// guarding it from deletion then.
+pub fn is_finished(cwd: &Path, cfg: &WgitConfig, branch: &str) -> Result<bool> {
+    if !is_started_flow_branch(cwd, branch)? {
+        return Ok(false);
+    }
+    let primary = primary_branch(cwd, cfg)?;
+    Ok(git::is_merged_by_commit(cwd, branch, &primary)?
+        || git::is_squash_merged(cwd, branch, &primary)?)
+}
+
+/// Where a new release branch is cut from: `develop` under git-flow,
+/// the primary branch under GitHub Flow.
+pub fn release_base(cwd: &Path, cfg: &WgitConfig) -> Result<String> {
+    match cfg.flow.model {
+        FlowModel::Github => primary_branch(cwd, cfg),
+        FlowModel::Gitflow => {
+            let develop = &cfg.flow.develop_branch;
+            if !git::branch_exists(cwd, develop)? {
+                bail!(
+                    "flow.develop_branch `{develop}` does not exist. create it from `{}` first (flow.model = \"gitflow\")",
+                    primary_branch(cwd, cfg)?
+                );
+            }
+            Ok(develop.clone())
+        }
+    }
+}
+
+/// Branches a finished release or hotfix must also be merged back into.
+/// GitHub Flow has none. Under git-flow a release goes back to `develop`;
+/// a hotfix goes to the active release branch(es), else to `develop`.
+pub fn back_merge_candidates(
+    cwd: &Path,
+    cfg: &WgitConfig,
+    kind: BranchKind,
+    source: &str,
+) -> Result<Vec<String>> {
+    if cfg.flow.model == FlowModel::Github {
+        return Ok(Vec::new());
+    }
+    if kind == BranchKind::Hotfix {
+        let releases: Vec<String> = git::list_local_branches(cwd)?
+            .into_iter()
+            .filter(|branch| branch != source && branch_kind(branch) == Some(BranchKind::Release))
+            .collect();
+        if !releases.is_empty() {
+            return Ok(releases);
+        }
+    }
+    let develop = &cfg.flow.develop_branch;
+    if git::branch_exists(cwd, develop)? {
+        return Ok(vec![develop.clone()]);
+    }
+    Ok(Vec::new())
+}
@@ -1,8 +1,8 @@
 use anyhow::{Context, Result, anyhow};
 use colored::Colorize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 #[derive(Debug)]
 pub struct GitOutput {
@@ -22,6 +22,46 @@ pub struct RemoteEntry {
     pub url: String,
 }
 
+#[derive(Clone, Debug)]
+pub struct TrackingBranch {
+    pub name: String,
+    pub upstream: Option<String>,
+    pub upstream_gone: bool,
+    pub ahead: usize,
+    pub behind: usize,
+    pub last_commit: String,
+    pub last_commit_unix: i64,
+}
+
+#[derive(Clone, Debug)]
+pub struct RemoteBranch {
+    pub remote: String,
+    pub branch: String,
+    pub last_commit: String,
+}
+
+impl RemoteBranch {
+    pub fn full_name(&self) -> String {
+        format!("{}/{}", self.remote, self.branch)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct StashEntry {
+    pub stash_ref: String,
+    pub id: String,
+    pub age: String,
+    pub branch: String,
+    pub message: String,
+}
+
+/// A commit listed oldest first for `wgit tidy` and `wgit backport`.
+#[derive(Clone, Debug)]
+pub struct CommitEntry {
+    pub hash: String,
+    pub subject: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct ReflogEntry {
     pub hash: String,
@@ -29,6 +69,16 @@ pub struct ReflogEntry {
 }
 
 static GIT_STEP_COUNTER: AtomicUsize = AtomicUsize::new(1);
+static ECHO_STEPS: AtomicBool = AtomicBool::new(true);
+
+/// Runs `f` without printing the git steps it takes; for read-only views
+/// such as `wgit status` whose own output explains the result.
+pub fn quietly<T>(f: impl FnOnce() -> T) -> T {
+    let previous = ECHO_STEPS.swap(false, Ordering::Relaxed);
+    let result = f();
+    ECHO_STEPS.store(previous, Ordering::Relaxed);
+    result
+}
 
 fn next_step_number() -> usize {
     GIT_STEP_COUNTER.fetch_add(1, Ordering::Relaxed)
@@ -48,9 +98,23 @@ fn infer_git_purpose(args: &[&str]) -> String {
     if args == ["diff", "--cached", "--name-only"] {
         return "List staged files before creating a commit.".to_string();
     }
+    if args.len() >= 4 && args[0] == "checkout" && args[1] == "-b" {
+        return "Create a new branch from the selected start point and switch to it.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "cherry-pick" && args[1] == "--abort" {
+        return "Cancel the cherry-pick and restore the branch.".to_string();
+    }
+    if args.len() >= 3 && args[0] == "cherry-pick" && args[1] == "-x" {
+        return "Copy selected commits onto this branch, noting the original commit ids."
+            .to_string();
+    }
     if args.len() >= 2 && args[0] == "checkout" && args[1] == "-b" {
         return "Create a new branch from current HEAD and switch to it.".to_string();
     }
+    if args.len() >= 2 && args[0] == "checkout" && args[1] == "--track" {
+        return "Create local branch tracking the selected remote branch and switch to it."
+            .to_string();
+    }
     if args.len() >= 2 && args[0] == "checkout" {
         return "Switch working branch to the selected target branch.".to_string();
     }
@@ -60,6 +124,12 @@ fn infer_git_purpose(args: &[&str]) -> String {
     if args.len() >= 2 && args[0] == "commit" && args[1] == "-m" {
         return "Create a commit with provided commit message.".to_string();
     }
+    if args.len() >= 3 && args[0] == "commit" && args[1] == "--amend" {
+        return "Replace the last commit with staged changes and the edited message.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "commit" && args[1].starts_with("--fixup=") {
+        return "Record staged changes as a fixup for an earlier commit.".to_string();
+    }
     if args.len() >= 2 && args[0] == "commit" && args[1] == "-F" {
         return "Create a commit from a prepared message file.".to_string();
     }
@@ -84,21 +154,73 @@ fn infer_git_purpose(args: &[&str]) -> String {
     if args.len() >= 2 && args[0] == "stash" && args[1] == "pop" {
         return "Restore previously stashed local changes.".to_string();
     }
+    if args.len() >= 2 && args[0] == "stash" && args[1] == "list" {
+        return "List saved stash entries.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "stash" && args[1] == "apply" {
+        return "Apply stashed changes while keeping the stash entry as a backup.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "stash" && args[1] == "drop" {
+        return "Remove stash entry that is no longer needed.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "stash" && args[1] == "store" {
+        return "Save stash commit again under a new message.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "stash" && args[1] == "show" {
+        return "Show changes saved in stash entry.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "stash" && args[1] == "branch" {
+        return "Create a new branch from stash base commit and apply the stash there."
+            .to_string();
+    }
+    if args.len() >= 3 && args[0] == "rev-parse" && args[2] == "refs/stash" {
+        return "Read commit id of the stash that was just created.".to_string();
+    }
     if args.len() >= 2 && args[0] == "pull" && args[1] == "--rebase" {
         return "Fetch remote updates and replay local commits on top.".to_string();
     }
+    if args.len() >= 2 && args[0] == "push" && args[1] == "--force-with-lease" {
+        return "Push rewritten branch only if remote still matches the last fetched state."
+            .to_string();
+    }
     if args.len() >= 2 && args[0] == "push" && args[1] == "-u" {
         return "Push branch and set upstream tracking for future sync.".to_string();
     }
+    if args.len() >= 3 && args[0] == "push" && args[2].starts_with("refs/tags/") {
+        return "Publish release tag to remote.".to_string();
+    }
     if args.len() >= 3 && args[0] == "push" && args[2] == "--delete" {
         return "Delete branch from selected remote repository.".to_string();
     }
     if args[0] == "push" {
         return "Push local commits to tracked remote branch.".to_string();
     }
+    if args.len() >= 2 && args[0] == "rebase" && args[1] == "-i" {
+        return "Run the interactive rebase with the plan prepared by wgit.".to_string();
+    }
+    if args.len() >= 4 && args[0] == "-c" && args[2] == "rebase" && args[3] == "-i" {
+        return "Fold fixup! commits into their targets without opening an editor.".to_string();
+    }
     if args.len() >= 2 && args[0] == "rebase" && args[1] == "--abort" {
         return "Cancel current rebase and return to previous state.".to_string();
     }
+    if args.len() >= 2 && args[0] == "rebase" && args[1] == "--continue" {
+        return "Continue rebase after conflicts were resolved.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "rebase" {
+        return "Replay current branch commits on top of the target branch.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "merge" && args[1] == "--no-ff" {
+        return "Merge branch with a merge commit, pausing before the commit is written."
+            .to_string();
+    }
+    if args.len() >= 2 && args[0] == "merge" && args[1] == "--ff-only" {
+        return "Fast-forward current branch to its upstream without a merge commit."
+            .to_string();
+    }
+    if args.len() >= 2 && args[0] == "merge" && args[1] == "--no-edit" {
+        return "Merge target branch into current branch with default message.".to_string();
+    }
     if args.len() >= 2 && args[0] == "merge" && args[1] == "--abort" {
         return "Cancel current merge and restore pre-merge state.".to_string();
     }
@@ -109,11 +231,27 @@ fn infer_git_purpose(args: &[&str]) -> String {
     if args.len() >= 2 && args[0] == "reset" && args[1] == "--soft" {
         return "Move branch pointer while keeping changes staged.".to_string();
     }
+    if args.len() >= 2 && args[0] == "log" && args[1] == "--format=%aN <%aE>" {
+        return "List recent commit authors to offer as co-authors.".to_string();
+    }
+    if args.len() >= 3 && args[0] == "log" && args[1] == "-1" && args[2].starts_with("--format=") {
+        return "Read commit message to prefill the editor.".to_string();
+    }
+    if args.len() >= 3 && args[0] == "branch" && args[1] == "-r" && args[2] == "--contains" {
+        return "Check whether the commit was already pushed to a remote.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "interpret-trailers" {
+        return "Append trailers to the commit message using git's trailer rules.".to_string();
+    }
+    if args.len() >= 3 && args[0] == "log" && args[1] == "--oneline" && !args[2].starts_with('-')
+    {
+        return "List commits that differ between local and remote branch.".to_string();
+    }
     if args.len() >= 2 && args[0] == "log" && args[1] == "--oneline" {
         return "Show recent commit history for rollback selection.".to_string();
     }
     if args.len() >= 2 && args[0] == "reflog" {
-        return "Show branch movement history for operation-based undo.".to_string();
+        return "Show branch movement history (checkouts, commits, resets).".to_string();
     }
     if args.len() >= 2 && args[0] == "tag" && args[1] == "--list" {
         return "Check whether target tag already exists.".to_string();
@@ -133,9 +271,52 @@ fn infer_git_purpose(args: &[&str]) -> String {
     if args.len() >= 3 && args[0] == "ls-remote" && args[1] == "--heads" {
         return "Check whether branch exists on selected remote.".to_string();
     }
+    if args.len() >= 3 && args[0] == "fetch" && args[1] == "." {
+        return "Fast-forward local branch to its upstream without checking it out.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "fetch" {
+        return "Download latest commits and refs from remote repositories.".to_string();
+    }
+    if args.len() >= 3 && args[0] == "rev-list" && args[1] == "--merges" {
+        return "Check the branch for merge commits that a linear rebase would drop.".to_string();
+    }
+    if args.len() >= 3 && args[0] == "log" && args[1] == "--reverse" {
+        return "List commits oldest first for selection.".to_string();
+    }
+    if args.len() >= 4 && args[0] == "rev-list" && args[1] == "--left-right" {
+        return "Count commits ahead of and behind the compared branch.".to_string();
+    }
+    if args.len() >= 3 && args[0] == "for-each-ref" && args[2] == "refs/heads" {
+        return "Read local branches with their upstream tracking state.".to_string();
+    }
+    if args.len() >= 3 && args[0] == "for-each-ref" && args[2] == "refs/remotes" {
+        return "List remote-tracking branches fetched from remotes.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "merge-base" && args[1] == "--is-ancestor" {
+        return "Check whether branch is already fully merged into target.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "merge-base" {
+        return "Find the commit where the branch split from its parent.".to_string();
+    }
+    if args.len() == 2 && args[0] == "rev-parse" && args[1].ends_with("^{tree}") {
+        return "Read file snapshot at branch tip.".to_string();
+    }
+    if args.len() >= 2 && args[0] == "commit-tree" {
+        return "Build a temporary squashed commit to compare with parent history."
+            .to_string();
+    }
+    if args.len() >= 2 && args[0] == "cherry" {
+        return "Check whether equivalent changes already exist in parent branch.".to_string();
+    }
     if args.len() >= 3 && args[0] == "config" && args[1] == "--get" {
         return "Read repository configuration value.".to_string();
     }
+    if args.len() == 3 && args[0] == "rev-parse" && args[1] == "--git-path" {
+        return "Locate wgit state file inside the git directory.".to_string();
+    }
+    if args.len() >= 4 && args[0] == "rev-parse" && args[1] == "--verify" && args[2] == "-q" {
+        return "Find the parent commit to start the rebase from.".to_string();
+    }
     if args.len() >= 3 && args[0] == "rev-parse" && args[1] == "--verify" && args[2] == "HEAD" {
         return "Check whether repository already has at least one commit.".to_string();
     }
@@ -147,6 +328,20 @@ fn infer_git_purpose(args: &[&str]) -> String {
     {
         return "Resolve upstream branch that current branch tracks.".to_string();
     }
+    if args.len() >= 3
+        && args[0] == "rev-parse"
+        && args[1] == "--abbrev-ref"
+        && args[2].ends_with("@{upstream}")
+    {
+        return "Resolve upstream branch that the selected branch tracks.".to_string();
+    }
+    if args.len() == 3 && args[0] == "config" && args[1].ends_with(".wgit-issue") {
+        return "Record linked issue so commits on this branch can reference it.".to_string();
+    }
+    if args.len() == 3 && args[0] == "config" && args[1].ends_with(".wgit-parent") {
+        return "Record parent branch so later commands know where this branch came from."
+            .to_string();
+    }
     if args.len() >= 3 && args[0] == "init" && args[1] == "-b" {
         return "Initialize a new repository and set default branch name.".to_string();
     }
@@ -185,20 +380,37 @@ fn print_block(label: &str, text: &str, is_stderr: bool) {
 }
 
 fn execute_git(args: &[&str], cwd: &Path, allow_fail: bool) -> Result<(bool, GitOutput)> {
-    let command_preview = format!("git {}", args.join(" "));
-    let step = next_step_number();
-    let purpose = infer_git_purpose(args);
-    println!(
-        "{}",
-        format!("[Step {step}] {}", purpose).truecolor(255, 187, 152)
-    );
-    println!(
-        "{}",
-        format!("Command: $ {command_preview}").truecolor(255, 187, 152)
-    );
+    execute_git_with_env(args, &[], cwd, allow_fail)
+}
+
+fn execute_git_with_env(
+    args: &[&str],
+    envs: &[(&str, &str)],
+    cwd: &Path,
+    allow_fail: bool,
+) -> Result<(bool, GitOutput)> {
+    let env_preview: String = envs
+        .iter()
+        .map(|(key, value)| format!("{key}=\"{value}\" "))
+        .collect();
+    let command_preview = format!("{env_preview}git {}", args.join(" "));
+    let echo = ECHO_STEPS.load(Ordering::Relaxed);
+    if echo {
+        let step = next_step_number();
+        let purpose = infer_git_purpose(args);
+        println!(
+            "{}",
+            format!("[Step {step}] {}", purpose).truecolor(255, 187, 152)
+        );
+        println!(
+            "{}",
+            format!("Command: $ {command_preview}").truecolor(255, 187, 152)
+        );
+    }
 
     let output = Command::new("git")
         .args(args)
+        .envs(envs.iter().copied())
         .current_dir(cwd)
         .output()
         .with_context(|| format!("failed to run git command: $ {command_preview}"))?;
@@ -210,20 +422,29 @@ fn execute_git(args: &[&str], cwd: &Path, allow_fail: bool) -> Result<(bool, Git
         .trim_end_matches(&['\r', '\n'][..])
         .to_string();
 
-    if !stdout.is_empty() {
-        print_block("Output", &stdout, false);
-    }
-    if !stderr.is_empty() {
-        print_block("Git notes", &stderr, true);
-    }
+    if echo {
+        if !stdout.is_empty() {
+            print_block("Output", &stdout, false);
+        }
+        if !stderr.is_empty() {
+            print_block("Git notes", &stderr, true);
+        }
 
-    if output.status.success() {
-        println!("{}", "Result: success".truecolor(125, 142, 149));
-    } else {
-        eprintln!("{}", "Result: failed".truecolor(125, 142, 149));
+        if output.status.success() {
+            println!("{}", "Result: success".truecolor(125, 142, 149));
+        } else {
+            eprintln!("{}", "Result: failed".truecolor(125, 142, 149));
+        }
     }
 
     if !output.status.success() && !allow_fail {
+        // Quiet runs never printed git's own explanation, so carry it along.
+        if !echo && !stderr.is_empty() {
+            return Err(anyhow!(
+                "git command failed: $ {command_preview}: {}",
+                stderr.trim()
+            ));
+        }
         return Err(anyhow!("git command failed: $ {command_preview}"));
     }
 
@@ -412,6 +633,162 @@ pub fn upstream_remote_and_branch(cwd: &Path) -> Result<Option<(String, String)>
     Ok(Some((remote.to_string(), branch.to_string())))
 }
 
+/// Upstream of an arbitrary local branch (e.g. `origin/main` for `main`).
+pub fn branch_upstream(cwd: &Path, branch: &str) -> Result<Option<String>> {
+    let spec = format!("{branch}@{{upstream}}");
+    let (ok, output) = run_git_allow_fail_in_dir(&["rev-parse", "--abbrev-ref", &spec], cwd)?;
+    if !ok || output.stdout.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(output.stdout.trim().to_string()))
+}
+
+/// Counts commits only in `branch` (ahead) and only in `base` (behind).
+pub fn ahead_behind(cwd: &Path, branch: &str, base: &str) -> Result<(usize, usize)> {
+    let range = format!("{branch}...{base}");
+    let output = run_git_in_dir(&["rev-list", "--left-right", "--count", &range], cwd)?;
+    let mut counts = output
+        .stdout
+        .split_whitespace()
+        .map(|value| value.parse::<usize>().unwrap_or(0));
+    let ahead = counts.next().unwrap_or(0);
+    let behind = counts.next().unwrap_or(0);
+    Ok((ahead, behind))
+}
+
+pub fn list_tracking_branches(cwd: &Path) -> Result<Vec<TrackingBranch>> {
+    let output = run_git_in_dir(
+        &[
+            "for-each-ref",
+            "--format=%(refname:short)%09%(upstream:short)%09%(upstream:track)%09%(committerdate:relative)%09%(committerdate:unix)",
+            "refs/heads",
+        ],
+        cwd,
+    )?;
+    let mut branches = Vec::new();
+    for line in output.stdout.lines().filter(|line| !line.trim().is_empty()) {
+        let mut fields = line.split('\t');
+        let name = fields.next().unwrap_or_default().trim().to_string();
+        let upstream = fields.next().unwrap_or_default().trim().to_string();
+        let track = fields.next().unwrap_or_default().trim();
+        let last_commit = fields.next().unwrap_or_default().trim().to_string();
+        let last_commit_unix = fields.next().unwrap_or_default().trim().parse().unwrap_or(0);
+        if name.is_empty() {
+            continue;
+        }
+        let (ahead, behind) = parse_track_counts(track);
+        branches.push(TrackingBranch {
+            name,
+            upstream: (!upstream.is_empty()).then_some(upstream),
+            upstream_gone: track == "[gone]",
+            ahead,
+            behind,
+            last_commit,
+            last_commit_unix,
+        });
+    }
+    Ok(branches)
+}
+
+/// Parses `%(upstream:track)` values such as `[ahead 1, behind 2]`.
+fn parse_track_counts(track: &str) -> (usize, usize) {
+    let inner = track.trim_start_matches('[').trim_end_matches(']');
+    let mut ahead = 0;
+    let mut behind = 0;
+    for part in inner.split(',').map(str::trim) {
+        if let Some(value) = part.strip_prefix("ahead ") {
+            ahead = value.parse().unwrap_or(0);
+        } else if let Some(value) = part.strip_prefix("behind ") {
+            behind = value.parse().unwrap_or(0);
+        }
+    }
+    (ahead, behind)
+}
+
+pub fn list_remote_branches(cwd: &Path) -> Result<Vec<RemoteBranch>> {
+    let output = run_git_in_dir(
+        &[
+            "for-each-ref",
+            "--format=%(refname)%09%(committerdate:relative)",
+            "refs/remotes",
+        ],
+        cwd,
+    )?;
+    let mut branches = Vec::new();
+    for line in output.stdout.lines().filter(|line| !line.trim().is_empty()) {
+        let (refname, last_commit) = line.split_once('\t').unwrap_or((line, ""));
+        let Some(short) = refname.trim().strip_prefix("refs/remotes/") else {
+            continue;
+        };
+        let Some((remote, branch)) = short.split_once('/') else {
+            continue;
+        };
+        if branch == "HEAD" {
+            continue;
+        }
+        branches.push(RemoteBranch {
+            remote: remote.to_string(),
+            branch: branch.to_string(),
+            last_commit: last_commit.trim().to_string(),
+        });
+    }
+    Ok(branches)
+}
+
+/// Branch names in the order they were last checked out, most recent first.
+pub fn recently_checked_out_branches(cwd: &Path, limit: usize) -> Result<Vec<String>> {
+    let mut seen = Vec::new();
+    for entry in list_reflog(cwd, limit)? {
+        let Some(rest) = entry.summary.strip_prefix("checkout: moving from ") else {
+            continue;
+        };
+        let Some((_, target)) = rest.rsplit_once(" to ") else {
+            continue;
+        };
+        let target = target.trim().to_string();
+        if !seen.contains(&target) {
+            seen.push(target);
+        }
+    }
+    Ok(seen)
+}
+
+pub fn checkout_tracking_branch(cwd: &Path, remote_branch: &str) -> Result<()> {
+    run_git_in_dir(&["checkout", "--track", remote_branch], cwd)?;
+    Ok(())
+}
+
+/// One-line summaries of commits selected by `revs` (e.g. `HEAD..origin/main`).
+pub fn list_commits(cwd: &Path, revs: &[&str]) -> Result<Vec<String>> {
+    let mut args = vec!["log", "--oneline"];
+    args.extend_from_slice(revs);
+    let output = run_git_in_dir(&args, cwd)?;
+    Ok(output
+        .stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(ToString::to_string)
+        .collect())
+}
+
+pub fn merge_ff_only(cwd: &Path, target: &str) -> Result<bool> {
+    let (ok, _) = run_git_allow_fail_in_dir(&["merge", "--ff-only", target], cwd)?;
+    Ok(ok)
+}
+
+/// Fast-forwards a branch that is not checked out; refuses non fast-forward updates.
+pub fn fast_forward_branch(cwd: &Path, branch: &str, upstream: &str) -> Result<bool> {
+    let refspec = format!("{upstream}:{branch}");
+    let (ok, _) = run_git_allow_fail_in_dir(&["fetch", ".", &refspec], cwd)?;
+    Ok(ok)
+}
+
+pub fn fetch_all(cwd: &Path) -> Result<()> {
+    run_git_in_dir(&["fetch", "--all", "--prune"], cwd)?;
+    Ok(())
+}
+
 pub fn default_branch(cwd: &Path) -> Result<String> {
     if branch_exists(cwd, "main")? {
         return Ok("main".to_string());
@@ -427,7 +804,44 @@ pub fn default_branch(cwd: &Path) -> Result<String> {
         .ok_or_else(|| anyhow!("no local branches found; create first commit and branch first"))
 }
 
+pub fn recorded_parent_branch(cwd: &Path, branch: &str) -> Result<Option<String>> {
+    let key = format!("branch.{branch}.wgit-parent");
+    let (ok, output) = run_git_allow_fail_in_dir(&["config", "--get", &key], cwd)?;
+    if !ok || output.stdout.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(output.stdout.trim().to_string()))
+}
+
+pub fn record_parent_branch(cwd: &Path, branch: &str, parent: &str) -> Result<()> {
+    let key = format!("branch.{branch}.wgit-parent");
+    run_git_in_dir(&["config", &key, parent], cwd)?;
+    Ok(())
+}
+
+pub fn recorded_branch_issue(cwd: &Path, branch: &str) -> Result<Option<String>> {
+    let key = format!("branch.{branch}.wgit-issue");
+    let (ok, output) = run_git_allow_fail_in_dir(&["config", "--get", &key], cwd)?;
+    if !ok || output.stdout.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(output.stdout.trim().to_string()))
+}
+
+pub fn record_branch_issue(cwd: &Path, branch: &str, issue: &str) -> Result<()> {
+    let key = format!("branch.{branch}.wgit-issue");
+    run_git_in_dir(&["config", &key, issue], cwd)?;
+    Ok(())
+}
+
 pub fn detect_parent_branch(cwd: &Path, current: &str) -> Result<String> {
+    if let Some(recorded) = recorded_parent_branch(cwd, current)?
+        && recorded != current
+        && branch_exists(cwd, &recorded)?
+    {
+        return Ok(recorded);
+    }
+
     if let Some(upstream) = upstream_branch(cwd)? {
         let upstream_short = upstream
             .split_once('/')
@@ -450,6 +864,65 @@ pub fn detect_parent_branch(cwd: &Path, current: &str) -> Result<String> {
         .ok_or_else(|| anyhow!("cannot find parent branch for {current}"))
 }
 
+/// Parent of any local branch: the one recorded by `wgit start`, else the default branch.
+pub fn parent_branch_for(cwd: &Path, branch: &str) -> Result<String> {
+    if let Some(recorded) = recorded_parent_branch(cwd, branch)?
+        && recorded != branch
+        && branch_exists(cwd, &recorded)?
+    {
+        return Ok(recorded);
+    }
+    default_branch(cwd)
+}
+
+pub fn is_ancestor(cwd: &Path, commit: &str, target: &str) -> Result<bool> {
+    let (ok, _) = run_git_allow_fail_in_dir(&["merge-base", "--is-ancestor", commit, target], cwd)?;
+    Ok(ok)
+}
+
+/// Detects a branch whose changes already landed in `target` without a
+/// merge: every commit has a patch-equivalent in `target` (rebase-merged),
+/// or the branch squashed onto its merge base does (squash-merged). A branch
+/// with no changes of its own beyond the merge base never counts.
+pub fn is_squash_merged(cwd: &Path, branch: &str, target: &str) -> Result<bool> {
+    let (ok, base) = run_git_allow_fail_in_dir(&["merge-base", target, branch], cwd)?;
+    let base = base.stdout.trim();
+    if !ok || base.is_empty() {
+        return Ok(false);
+    }
+    let tree_spec = format!("{branch}^{{tree}}");
+    let tree = run_git_in_dir(&["rev-parse", &tree_spec], cwd)?;
+    let base_tree_spec = format!("{base}^{{tree}}");
+    let base_tree = run_git_in_dir(&["rev-parse", &base_tree_spec], cwd)?;
+    if tree.stdout.trim() == base_tree.stdout.trim() {
+        return Ok(false);
+    }
+
+    let cherry = run_git_in_dir(&["cherry", target, branch, base], cwd)?;
+    let marks: Vec<&str> = cherry
+        .stdout
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    if !marks.is_empty() && marks.iter().all(|line| line.starts_with('-')) {
+        return Ok(true);
+    }
+
+    let squashed = run_git_in_dir(
+        &[
+            "commit-tree",
+            tree.stdout.trim(),
+            "-p",
+            base,
+            "-m",
+            "wgit squash probe",
+        ],
+        cwd,
+    )?;
+    let cherry = run_git_in_dir(&["cherry", target, squashed.stdout.trim(), base], cwd)?;
+    Ok(cherry.stdout.trim_start().starts_with('-'))
+}
+
 pub fn checkout_branch(cwd: &Path, branch: &str) -> Result<()> {
     run_git_in_dir(&["checkout", branch], cwd)?;
     Ok(())
@@ -460,6 +933,247 @@ pub fn squash_merge_branch(cwd: &Path, branch: &str) -> Result<()> {
     Ok(())
 }
 
+/// Merges `branch` with a merge commit, stopping before the commit so the
+/// guided message editor can write it.
+pub fn merge_no_commit(cwd: &Path, branch: &str) -> Result<()> {
+    run_git_in_dir(&["merge", "--no-ff", "--no-commit", branch], cwd)?;
+    Ok(())
+}
+
+/// `Name <email>` from `user.name`/`user.email`, used for `Signed-off-by`.
+pub fn user_identity(cwd: &Path) -> Result<Option<String>> {
+    let (name_ok, name) = run_git_allow_fail_in_dir(&["config", "--get", "user.name"], cwd)?;
+    let (email_ok, email) = run_git_allow_fail_in_dir(&["config", "--get", "user.email"], cwd)?;
+    let (name, email) = (name.stdout.trim(), email.stdout.trim());
+    if !name_ok || !email_ok || name.is_empty() || email.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(format!("{name} <{email}>")))
+}
+
+/// Distinct `Name <email>` authors from recent history, most recent first.
+pub fn recent_authors(cwd: &Path, limit: usize) -> Result<Vec<String>> {
+    let (ok, output) = run_git_allow_fail_in_dir(&["log", "--format=%aN <%aE>", "-n", "300"], cwd)?;
+    if !ok {
+        return Ok(Vec::new());
+    }
+    let mut authors: Vec<String> = Vec::new();
+    for line in output.stdout.lines().map(str::trim) {
+        if !line.is_empty() && !authors.iter().any(|author| author == line) {
+            authors.push(line.to_string());
+        }
+        if authors.len() >= limit {
+            break;
+        }
+    }
+    Ok(authors)
+}
+
+/// Returns the message in `message_file` with `trailers` appended by
+/// `git interpret-trailers`, so they land in a single, parseable trailer block.
+pub fn interpret_trailers(
+    cwd: &Path,
+    message_file: &str,
+    trailers: &[(String, String)],
+) -> Result<String> {
+    let rendered: Vec<String> = trailers
+        .iter()
+        .map(|(key, value)| format!("{key}: {value}"))
+        .collect();
+    let mut args = vec!["interpret-trailers", "--if-exists", "addIfDifferent"];
+    for trailer in &rendered {
+        args.push("--trailer");
+        args.push(trailer);
+    }
+    args.push(message_file);
+    Ok(run_git_in_dir(&args, cwd)?.stdout)
+}
+
+/// Full message of `rev`.
+pub fn commit_message(cwd: &Path, rev: &str) -> Result<String> {
+    Ok(run_git_in_dir(&["log", "-1", "--format=%B", rev], cwd)?.stdout)
+}
+
+/// `(key, value)` trailers of `rev`, with folded values unfolded.
+pub fn commit_trailers(cwd: &Path, rev: &str) -> Result<Vec<(String, String)>> {
+    let output = run_git_in_dir(&["log", "-1", "--format=%(trailers:only,unfold)", rev], cwd)?;
+    Ok(output
+        .stdout
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect())
+}
+
+/// Remote-tracking branches that already contain `commit`.
+pub fn remote_branches_containing(cwd: &Path, commit: &str) -> Result<Vec<String>> {
+    let (ok, output) = run_git_allow_fail_in_dir(&["branch", "-r", "--contains", commit], cwd)?;
+    if !ok {
+        return Ok(Vec::new());
+    }
+    Ok(output
+        .stdout
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.contains(" -> "))
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Commits selected by `revs` (e.g. `main..HEAD` or `-n 30 main`), oldest first.
+pub fn list_commit_entries(cwd: &Path, revs: &[&str]) -> Result<Vec<CommitEntry>> {
+    let mut args = vec!["log", "--reverse", "--format=%H%x09%s"];
+    args.extend_from_slice(revs);
+    let output = run_git_in_dir(&args, cwd)?;
+    Ok(output
+        .stdout
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(hash, subject)| CommitEntry {
+            hash: hash.trim().to_string(),
+            subject: subject.to_string(),
+        })
+        .collect())
+}
+
+/// Commit `branch` pointed at when it was created, read from its reflog.
+/// `None` when the reflog is missing or expired.
+pub fn branch_created_at(cwd: &Path, branch: &str) -> Result<Option<String>> {
+    let reference = format!("refs/heads/{branch}");
+    let (ok, output) =
+        run_git_allow_fail_in_dir(&["reflog", "show", "--format=%H", &reference, "--"], cwd)?;
+    if !ok {
+        return Ok(None);
+    }
+    Ok(output.stdout.lines().last().map(|hash| hash.trim().to_string()))
+}
+
+/// Whether `branch`'s tip was merged into `target` by a merge commit,
+/// i.e. it is a non-first parent of a merge in `target`'s history.
+pub fn is_merged_by_commit(cwd: &Path, branch: &str, target: &str) -> Result<bool> {
+    let tip = run_git_in_dir(&["rev-parse", branch], cwd)?;
+    let tip = tip.stdout.trim();
+    let range = format!("{branch}..{target}");
+    let merges = run_git_in_dir(&["rev-list", "--merges", "--parents", &range], cwd)?;
+    Ok(merges
+        .stdout
+        .lines()
+        .any(|line| line.split_whitespace().skip(2).any(|parent| parent == tip)))
+}
+
+pub fn count_merge_commits(cwd: &Path, range: &str) -> Result<usize> {
+    let output = run_git_in_dir(&["rev-list", "--merges", "--count", range], cwd)?;
+    Ok(output.stdout.trim().parse().unwrap_or(0))
+}
+
+pub fn merge_base(cwd: &Path, left: &str, right: &str) -> Result<String> {
+    Ok(run_git_in_dir(&["merge-base", left, right], cwd)?
+        .stdout
+        .trim()
+        .to_string())
+}
+
+/// Single-quotes `value` for the `sh` that git runs editors and `exec`
+/// lines with, so paths containing spaces or quotes stay one argument.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Runs `rebase -i base` with `todo_file` copied in as the todo list.
+/// `GIT_EDITOR=true` keeps git's combined message for squashes.
+/// Returns `false` when the rebase stopped (conflict or failed step).
+pub fn rebase_with_todo(cwd: &Path, base: &str, todo_file: &str) -> Result<bool> {
+    let sequence_editor = format!("cp {}", shell_quote(todo_file));
+    let (ok, _) = execute_git_with_env(
+        &["rebase", "-i", base],
+        &[
+            ("GIT_SEQUENCE_EDITOR", sequence_editor.as_str()),
+            ("GIT_EDITOR", "true"),
+        ],
+        cwd,
+        true,
+    )?;
+    Ok(ok)
+}
+
+pub fn create_branch_from(cwd: &Path, branch: &str, start_point: &str) -> Result<()> {
+    run_git_in_dir(&["checkout", "-b", branch, start_point], cwd)?;
+    Ok(())
+}
+
+/// Cherry-picks `commits` in order with `-x`. Returns `false` on conflict
+/// (or an empty pick when the change already exists on the branch).
+pub fn cherry_pick(cwd: &Path, commits: &[&str]) -> Result<bool> {
+    let mut args = vec!["cherry-pick", "-x"];
+    args.extend_from_slice(commits);
+    let (ok, _) = run_git_allow_fail_in_dir(&args, cwd)?;
+    Ok(ok)
+}
+
+pub fn cherry_pick_abort(cwd: &Path) -> Result<()> {
+    run_git_in_dir(&["cherry-pick", "--abort"], cwd)?;
+    Ok(())
+}
+
+pub fn commit_amend_from_file(cwd: &Path, message_file: &str) -> Result<()> {
+    run_git_in_dir(&["commit", "--amend", "-F", message_file], cwd)?;
+    Ok(())
+}
+
+pub fn commit_fixup(cwd: &Path, target: &str) -> Result<()> {
+    let fixup = format!("--fixup={target}");
+    run_git_in_dir(&["commit", &fixup], cwd)?;
+    Ok(())
+}
+
+/// Runs `rebase -i --autosquash` with a no-op sequence editor so the
+/// generated todo list is used as is. `base: None` rebases from the root.
+/// The editor goes through `GIT_SEQUENCE_EDITOR`, which an exported value
+/// would otherwise override. Returns `false` when the rebase stopped on conflicts.
+pub fn autosquash(cwd: &Path, base: Option<&str>) -> Result<bool> {
+    let (ok, _) = execute_git_with_env(
+        &[
+            "rebase",
+            "-i",
+            "--autosquash",
+            "--autostash",
+            base.unwrap_or("--root"),
+        ],
+        &[("GIT_SEQUENCE_EDITOR", "true")],
+        cwd,
+        true,
+    )?;
+    Ok(ok)
+}
+
+/// Parent of `commit`, or `None` for a root commit.
+pub fn parent_commit(cwd: &Path, commit: &str) -> Result<Option<String>> {
+    let spec = format!("{commit}^");
+    let (ok, output) = run_git_allow_fail_in_dir(&["rev-parse", "--verify", "-q", &spec], cwd)?;
+    if !ok || output.stdout.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(output.stdout.trim().to_string()))
+}
+
+/// Second parent of `commit` when it is a merge commit.
+pub fn merged_parent(cwd: &Path, commit: &str) -> Result<Option<String>> {
+    let spec = format!("{commit}^2");
+    let (ok, output) = run_git_allow_fail_in_dir(&["rev-parse", "--verify", "-q", &spec], cwd)?;
+    if !ok || output.stdout.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(output.stdout.trim().to_string()))
+}
+
+pub fn stage_paths(cwd: &Path, paths: &[String]) -> Result<()> {
+    let mut args = vec!["add", "--"];
+    args.extend(paths.iter().map(String::as_str));
+    run_git_in_dir(&args, cwd)?;
+    Ok(())
+}
+
 pub fn commit_with_message(cwd: &Path, message: &str) -> Result<()> {
     run_git_in_dir(&["commit", "-m", message], cwd)?;
     Ok(())
@@ -546,17 +1260,135 @@ pub fn has_uncommitted_changes(cwd: &Path) -> Result<bool> {
     Ok(!working_tree_entries(cwd)?.is_empty())
 }
 
-pub fn stash_push(cwd: &Path, message: &str) -> Result<bool> {
-    let output = run_git_in_dir(&["stash", "push", "-u", "-m", message], cwd)?;
+/// Stashes local changes; returns the stash commit id when something was saved.
+pub fn stash_push(cwd: &Path, message: &str, include_untracked: bool) -> Result<Option<String>> {
+    let mut args = vec!["stash", "push"];
+    if include_untracked {
+        args.push("-u");
+    }
+    args.extend_from_slice(&["-m", message]);
+    let output = run_git_in_dir(&args, cwd)?;
     let text = output.stdout.to_lowercase();
-    Ok(!text.contains("no local changes to save"))
+    if text.contains("no local changes to save") {
+        return Ok(None);
+    }
+    let id = run_git_in_dir(&["rev-parse", "--verify", "refs/stash"], cwd)?;
+    Ok(Some(id.stdout.trim().to_string()))
 }
 
-pub fn stash_pop(cwd: &Path) -> Result<()> {
-    run_git_in_dir(&["stash", "pop"], cwd)?;
+/// Returns `false` when the stash could not be applied cleanly; Git keeps the entry then.
+pub fn stash_pop(cwd: &Path, stash_ref: &str) -> Result<bool> {
+    let (ok, _) = run_git_allow_fail_in_dir(&["stash", "pop", stash_ref], cwd)?;
+    Ok(ok)
+}
+
+/// Applies a stash by ref or commit id without dropping it.
+pub fn stash_apply(cwd: &Path, stash: &str) -> Result<bool> {
+    let (ok, _) = run_git_allow_fail_in_dir(&["stash", "apply", stash], cwd)?;
+    Ok(ok)
+}
+
+pub fn stash_drop(cwd: &Path, stash_ref: &str) -> Result<()> {
+    run_git_in_dir(&["stash", "drop", stash_ref], cwd)?;
     Ok(())
 }
 
+/// Adds an existing stash commit back to the stash list with `message`.
+pub fn stash_store(cwd: &Path, id: &str, message: &str) -> Result<()> {
+    run_git_in_dir(&["stash", "store", "-m", message, id], cwd)?;
+    Ok(())
+}
+
+pub fn stash_branch(cwd: &Path, branch: &str, stash_ref: &str) -> Result<()> {
+    run_git_in_dir(&["stash", "branch", branch, stash_ref], cwd)?;
+    Ok(())
+}
+
+/// Full patch of a stash, including untracked files saved with `-u`.
+pub fn stash_show_patch(cwd: &Path, stash_ref: &str) -> Result<String> {
+    let output = run_git_in_dir(&["stash", "show", "-p", "--include-untracked", stash_ref], cwd)?;
+    Ok(output.stdout)
+}
+
+pub fn list_stashes(cwd: &Path) -> Result<Vec<StashEntry>> {
+    let output = run_git_in_dir(&["stash", "list", "--format=%gd%x09%H%x09%cr%x09%gs"], cwd)?;
+    let mut entries = Vec::new();
+    for line in output.stdout.lines().filter(|line| !line.trim().is_empty()) {
+        let mut fields = line.splitn(4, '\t');
+        let (Some(stash_ref), Some(id), Some(age), Some(subject)) =
+            (fields.next(), fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        // Reflog subjects look like `On main: msg` or `WIP on main: abc1234 msg`.
+        let (branch, message) = subject
+            .strip_prefix("WIP on ")
+            .or_else(|| subject.strip_prefix("On "))
+            .and_then(|rest| rest.split_once(": "))
+            .map_or_else(
+                || (String::new(), subject.to_string()),
+                |(branch, message)| (branch.to_string(), message.to_string()),
+            );
+        entries.push(StashEntry {
+            stash_ref: stash_ref.to_string(),
+            id: id.to_string(),
+            age: age.to_string(),
+            branch,
+            message,
+        });
+    }
+    Ok(entries)
+}
+
+/// Resolves a stash commit id to its current `stash@{n}` position, which shifts
+/// whenever other stashes are pushed or dropped.
+pub fn stash_ref_for_id(cwd: &Path, id: &str) -> Result<Option<String>> {
+    let output = run_git_in_dir(&["stash", "list", "--format=%gd %H"], cwd)?;
+    Ok(output.stdout.lines().find_map(|line| {
+        let (stash_ref, hash) = line.trim().split_once(' ')?;
+        (hash == id).then(|| stash_ref.to_string())
+    }))
+}
+
+/// `name` inside the repository's git directory, which is not `.git/` in a
+/// linked worktree or a submodule.
+pub fn git_path(cwd: &Path, name: &str) -> Result<PathBuf> {
+    let output = run_git_in_dir(&["rev-parse", "--git-path", name], cwd)?;
+    Ok(cwd.join(output.stdout.trim()))
+}
+
+pub fn rebase_in_progress(cwd: &Path) -> bool {
+    let git_dir = cwd.join(".git");
+    git_dir.join("rebase-merge").exists() || git_dir.join("rebase-apply").exists()
+}
+
+/// Branch being rebased, read from the rebase state while HEAD is detached.
+pub fn rebasing_branch(cwd: &Path) -> Option<String> {
+    let git_dir = cwd.join(".git");
+    ["rebase-merge", "rebase-apply"].iter().find_map(|dir| {
+        let head = std::fs::read_to_string(git_dir.join(dir).join("head-name")).ok()?;
+        head.trim()
+            .strip_prefix("refs/heads/")
+            .map(ToString::to_string)
+    })
+}
+
+/// Name of the multi-step operation waiting to be continued or aborted.
+pub fn operation_in_progress(cwd: &Path) -> Option<&'static str> {
+    let git_dir = cwd.join(".git");
+    if rebase_in_progress(cwd) {
+        Some("rebase")
+    } else if git_dir.join("MERGE_HEAD").exists() {
+        Some("merge")
+    } else if git_dir.join("CHERRY_PICK_HEAD").exists() {
+        Some("cherry-pick")
+    } else if git_dir.join("REVERT_HEAD").exists() {
+        Some("revert")
+    } else {
+        None
+    }
+}
+
 pub fn pull_rebase(cwd: &Path, remote: Option<&str>, branch: Option<&str>) -> Result<()> {
     let mut args = vec!["pull", "--rebase"];
     if let Some(r) = remote {
@@ -578,6 +1410,27 @@ pub fn push_current(cwd: &Path, set_upstream_remote: Option<&str>, branch: &str)
     Ok(())
 }
 
+pub fn push_tag(cwd: &Path, remote: &str, tag: &str) -> Result<()> {
+    let refspec = format!("refs/tags/{tag}");
+    run_git_in_dir(&["push", remote, &refspec], cwd)?;
+    Ok(())
+}
+
+pub fn push_force_with_lease(cwd: &Path) -> Result<()> {
+    run_git_in_dir(&["push", "--force-with-lease"], cwd)?;
+    Ok(())
+}
+
+pub fn rebase_onto(cwd: &Path, base: &str) -> Result<()> {
+    run_git_in_dir(&["rebase", base], cwd)?;
+    Ok(())
+}
+
+pub fn merge_no_edit(cwd: &Path, branch: &str) -> Result<()> {
+    run_git_in_dir(&["merge", "--no-edit", branch], cwd)?;
+    Ok(())
+}
+
 pub fn rebase_abort(cwd: &Path) -> Result<()> {
     run_git_in_dir(&["rebase", "--abort"], cwd)?;
     Ok(())
@@ -643,7 +1496,12 @@ pub fn reset_to(cwd: &Path, target: &str, hard: bool) -> Result<()> {
 }
 
 pub fn latest_tag(cwd: &Path) -> Result<Option<String>> {
-    let (ok, output) = run_git_allow_fail_in_dir(&["describe", "--tags", "--abbrev=0"], cwd)?;
+    latest_tag_on(cwd, "HEAD")
+}
+
+/// Latest tag reachable from `rev`.
+pub fn latest_tag_on(cwd: &Path, rev: &str) -> Result<Option<String>> {
+    let (ok, output) = run_git_allow_fail_in_dir(&["describe", "--tags", "--abbrev=0", rev], cwd)?;
     if !ok || output.stdout.trim().is_empty() {
         return Ok(None);
     }
@@ -660,6 +1518,15 @@ pub fn create_tag(cwd: &Path, tag: &str) -> Result<()> {
     Ok(())
 }
 
+/// Abbreviated HEAD commit, or `None` before the first commit.
+pub fn short_head(cwd: &Path) -> Result<Option<String>> {
+    let (ok, output) = run_git_allow_fail_in_dir(&["rev-parse", "--short", "HEAD"], cwd)?;
+    if !ok || output.stdout.trim().is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(output.stdout.trim().to_string()))
+}
+
 pub fn has_commits(cwd: &Path) -> Result<bool> {
     let (ok, _) = run_git_allow_fail_in_dir(&["rev-parse", "--verify", "HEAD"], cwd)?;
     Ok(ok)
@@ -0,0 +1,283 @@
+use crate::git;
+use anyhow::{Context, Result, bail};
+use reqwest::Url;
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+
+pub const GITHUB_API: &str = "https://api.github.com";
+/// Host GitHub hands out in `upload_url` for release assets.
+const GITHUB_UPLOADS: &str = "https://uploads.github.com";
+
+/// `owner/name` of the origin remote when it points at GitHub.
+pub fn origin_repo(cwd: &Path) -> Result<Option<String>> {
+    Ok(git::origin_remote_url(cwd)?
+        .as_deref()
+        .and_then(git::github_repo_slug_from_url))
+}
+
+#[derive(Debug, Deserialize)]
+struct IssueResponse {
+    title: String,
+}
+
+/// The issue title lookup is optional; `wgit start` must not hang on it.
+const ISSUE_TITLE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Fetches an issue title from `{api_base}/repos/{repo}/issues/{number}`.
+pub fn fetch_issue_title(api_base: &str, repo: &str, number: u64) -> Result<String> {
+    let url = format!("{api_base}/repos/{repo}/issues/{number}");
+    let client = Client::builder()
+        .timeout(ISSUE_TITLE_TIMEOUT)
+        .build()
+        .context("failed to build HTTP client")?;
+    let issue = with_auth_for(client.get(&url), &url, api_base)
+        .header("User-Agent", "wgit")
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .with_context(|| format!("failed to request issue: {url}"))?
+        .error_for_status()
+        .with_context(|| format!("GitHub API returned non-success status for issue #{number}"))?
+        .json::<IssueResponse>()
+        .context("failed to parse issue response")?;
+    Ok(issue.title)
+}
+
+#[derive(Debug, Serialize)]
+struct PullRequestPayload<'a> {
+    title: &'a str,
+    head: &'a str,
+    base: &'a str,
+    body: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct PullRequestResponse {
+    html_url: String,
+}
+
+/// Opens a pull request from `head` into `base` and returns its URL.
+/// Needs `GITHUB_TOKEN` with write access to the repository.
+pub fn create_pull_request(
+    api_base: &str,
+    repo: &str,
+    head: &str,
+    base: &str,
+    title: &str,
+    body: &str,
+) -> Result<String> {
+    if !has_token() {
+        bail!("GITHUB_TOKEN is not set; it is required to open pull requests");
+    }
+    let url = format!("{api_base}/repos/{repo}/pulls");
+    let client = Client::builder()
+        .build()
+        .context("failed to build HTTP client")?;
+    let response = with_auth(client.post(&url))
+        .header("User-Agent", "wgit")
+        .header("Accept", "application/vnd.github+json")
+        .json(&PullRequestPayload {
+            title,
+            head,
+            base,
+            body,
+        })
+        .send()
+        .with_context(|| format!("failed to request pull request creation: {url}"))?
+        .error_for_status()
+        .with_context(|| format!("GitHub API rejected pull request `{head}` -> `{base}`"))?
+        .json::<PullRequestResponse>()
+        .context("failed to parse pull request response")?;
+    Ok(response.html_url)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub assets: Vec<ReleaseAsset>,
+    #[serde(default)]
+    pub draft: bool,
+    #[serde(default)]
+    pub prerelease: bool,
+}
+
+/// Fetches `{releases_url}/latest`, e.g. `https://api.github.com/repos/o/r/releases/latest`.
+/// GitHub only returns published, non-prerelease releases here.
+/// `api_base` is the only non-default host trusted with `GITHUB_TOKEN`;
+/// `None` sends no token at all.
+pub fn fetch_latest_release(
+    releases_url: &str,
+    api_base: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<Release> {
+    get_release_json(
+        &format!("{releases_url}/latest"),
+        "latest release",
+        api_base,
+        timeout,
+    )
+}
+
+/// Fetches the release for `tag` from `{releases_url}/tags/{tag}`.
+pub fn fetch_release_by_tag(
+    releases_url: &str,
+    tag: &str,
+    api_base: Option<&str>,
+) -> Result<Release> {
+    get_release_json(
+        &format!("{releases_url}/tags/{tag}"),
+        &format!("release `{tag}`"),
+        api_base,
+        None,
+    )
+}
+
+/// Most recent releases, including pre-releases and drafts.
+pub fn fetch_releases(
+    releases_url: &str,
+    api_base: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<Vec<Release>> {
+    get_release_json(
+        &format!("{releases_url}?per_page=50"),
+        "release list",
+        api_base,
+        timeout,
+    )
+}
+
+fn get_release_json<T: serde::de::DeserializeOwned>(
+    url: &str,
+    what: &str,
+    api_base: Option<&str>,
+    timeout: Option<Duration>,
+) -> Result<T> {
+    let mut builder = Client::builder();
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    let client = builder.build().context("failed to build HTTP client")?;
+    let mut request = client.get(url);
+    // A release mirror (`update.base_url`) must never see the token.
+    if let Some(api_base) = api_base {
+        request = with_auth_for(request, url, api_base);
+    }
+    request
+        .header("User-Agent", "wgit-updater")
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .with_context(|| format!("failed to request {what}: {url}"))?
+        .error_for_status()
+        .with_context(|| format!("GitHub API returned non-success status for {what}"))?
+        .json::<T>()
+        .with_context(|| format!("failed to parse {what} response"))
+}
+
+/// Whether `url` is served by `api.github.com`, GitHub's uploads host or
+/// `api_base` (same scheme, host and port).
+fn is_github_api(url: &str, api_base: &str) -> bool {
+    let origin = |value: &str| Url::parse(value).ok().map(|url| url.origin());
+    let Some(target) = origin(url).filter(|origin| origin.is_tuple()) else {
+        return false;
+    };
+    [GITHUB_API, GITHUB_UPLOADS, api_base]
+        .into_iter()
+        .any(|trusted| origin(trusted).as_ref() == Some(&target))
+}
+
+#[derive(Debug, Serialize)]
+pub struct NewRelease<'a> {
+    pub tag_name: &'a str,
+    pub name: &'a str,
+    pub body: &'a str,
+    pub draft: bool,
+    pub prerelease: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatedRelease {
+    pub html_url: String,
+    /// Hypermedia template such as `https://uploads.github.com/.../assets{?name,label}`.
+    upload_url: String,
+}
+
+/// Creates a release for an already pushed tag. Needs `GITHUB_TOKEN`.
+pub fn create_release(api_base: &str, repo: &str, release: &NewRelease) -> Result<CreatedRelease> {
+    if !has_token() {
+        bail!("GITHUB_TOKEN is not set; it is required to publish releases");
+    }
+    let url = format!("{api_base}/repos/{repo}/releases");
+    let client = Client::builder()
+        .build()
+        .context("failed to build HTTP client")?;
+    with_auth_for(client.post(&url), &url, api_base)
+        .header("User-Agent", "wgit")
+        .header("Accept", "application/vnd.github+json")
+        .json(release)
+        .send()
+        .with_context(|| format!("failed to request release creation: {url}"))?
+        .error_for_status()
+        .with_context(|| format!("GitHub API rejected release `{}`", release.tag_name))?
+        .json::<CreatedRelease>()
+        .context("failed to parse release response")
+}
+
+/// Uploads one asset to the URL GitHub returned for the release, so a
+/// local API stub can also stand in for the upload host. The token only
+/// goes to `api_base` or GitHub's uploads host.
+pub fn upload_release_asset(
+    api_base: &str,
+    release: &CreatedRelease,
+    name: &str,
+    bytes: Vec<u8>,
+) -> Result<()> {
+    let base = release
+        .upload_url
+        .split_once('{')
+        .map_or(release.upload_url.as_str(), |(base, _)| base);
+    if !is_github_api(base, api_base) {
+        bail!("release upload host is neither GitHub nor `{api_base}`: {base}");
+    }
+    let client = Client::builder()
+        .build()
+        .context("failed to build HTTP client")?;
+    with_auth_for(client.post(base), base, api_base)
+        .query(&[("name", name)])
+        .header("User-Agent", "wgit")
+        .header("Accept", "application/vnd.github+json")
+        .header("Content-Type", "application/octet-stream")
+        .body(bytes)
+        .send()
+        .with_context(|| format!("failed to upload release asset: {name}"))?
+        .error_for_status()
+        .with_context(|| format!("GitHub API rejected release asset `{name}`"))?;
+    Ok(())
+}
+
+pub fn has_token() -> bool {
+    std::env::var("GITHUB_TOKEN").is_ok_and(|token| !token.trim().is_empty())
+}
+
+/// Attaches `GITHUB_TOKEN` only when `url` passes [`is_github_api`].
+fn with_auth_for(request: RequestBuilder, url: &str, api_base: &str) -> RequestBuilder {
+    if is_github_api(url, api_base) {
+        with_auth(request)
+    } else {
+        request
+    }
+}
+
+/// Private repositories need `GITHUB_TOKEN`; public ones work without it.
+fn with_auth(request: RequestBuilder) -> RequestBuilder {
+    match std::env::var("GITHUB_TOKEN") {
+        Ok(token) if !token.trim().is_empty() => request.bearer_auth(token.trim()),
+        _ => request,
+    }
+}
@@ -1,10 +1,19 @@
+mod archive;
 mod cli;
 mod commands;
 mod config;
+mod convention;
+mod flow;
 mod git;
+mod github;
+mod policy;
+mod release;
+mod settings;
+mod trailers;
 mod utils;
 
 use anyhow::Result;
+use std::path::Path;
 
 fn main() {
     if let Err(error) = run() {
@@ -15,5 +24,22 @@ fn main() {
 
 fn run() -> Result<()> {
     let cli = cli::parse();
-    commands::dispatch(cli.command)
+    // The detached update check has no terminal and must not touch the worktree.
+    let background = matches!(
+        cli.command,
+        Some(cli::Command::Update {
+            background_check: true,
+            ..
+        })
+    );
+    if !background && let Err(error) = commands::sync::restore_pending_auto_stash(Path::new(".")) {
+        eprintln!("Warning: could not restore pending sync auto-stash: {error:#}");
+    }
+    // `update` reports versions itself; the notice would only repeat it.
+    let notify = !matches!(cli.command, Some(cli::Command::Update { .. }));
+    commands::dispatch(cli.command)?;
+    if notify {
+        commands::update::notify_if_outdated();
+    }
+    Ok(())
 }
@@ -0,0 +1,179 @@
+use crate::config::{self, BranchRules, WgitConfig};
+use anyhow::Result;
+use std::path::Path;
+
+/// Operations that protected branch rules can restrict.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Stage or commit directly on the branch (also covers merging into it locally).
+    Commit,
+    /// Rewrite the remote branch with `--force-with-lease`.
+    ForcePush,
+    /// Run `wgit finish` with this branch as the source.
+    FinishFrom,
+    /// Delete the branch locally or remotely.
+    Delete,
+}
+
+/// Effective rules for one branch, resolved from `[safety]` config.
+#[derive(Clone, Debug)]
+pub struct BranchPolicy {
+    pub branch: String,
+    /// Protected pattern that matched, or `None` for an unprotected branch.
+    pub pattern: Option<String>,
+    pub rules: BranchRules,
+}
+
+impl BranchPolicy {
+    pub fn is_protected(&self) -> bool {
+        self.pattern.is_some()
+    }
+
+    /// Explains why `action` is blocked on this branch, if it is.
+    pub fn denies(&self, action: Action) -> Option<String> {
+        let pattern = self.pattern.as_deref()?;
+        let branch = &self.branch;
+        let rule = if pattern == branch {
+            String::new()
+        } else {
+            format!(" (rule `{pattern}`)")
+        };
+        match action {
+            Action::Commit if !self.rules.allow_direct_commits => Some(format!(
+                "Direct commits are not allowed on protected branch `{branch}`{rule}."
+            )),
+            Action::ForcePush if !self.rules.allow_force_push => Some(format!(
+                "Force push is not allowed on protected branch `{branch}`{rule}."
+            )),
+            Action::FinishFrom => Some(format!(
+                "Finish is not allowed on protected branch `{branch}`{rule}."
+            )),
+            Action::Delete => Some(format!(
+                "Deleting protected branch `{branch}` is blocked{rule}."
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Loads config and resolves the policy for `branch`.
+pub fn evaluate(repo_root: &Path, branch: &str) -> Result<BranchPolicy> {
+    let cfg = config::load_config(repo_root)?;
+    Ok(evaluate_with(&cfg, branch))
+}
+
+/// Resolves the policy for `branch` with an already loaded config.
+/// Exact names win over glob patterns; otherwise the first matching pattern applies.
+pub fn evaluate_with(cfg: &WgitConfig, branch: &str) -> BranchPolicy {
+    let branch = branch.trim();
+    let pattern = cfg
+        .protected_branches
+        .iter()
+        .find(|pattern| pattern.as_str() == branch)
+        .or_else(|| {
+            cfg.protected_branches
+                .iter()
+                .find(|pattern| glob_match(pattern, branch))
+        })
+        .cloned();
+
+    let rules = match &pattern {
+        Some(pattern) => cfg
+            .branch_rules
+            .get(pattern)
+            .cloned()
+            .unwrap_or_else(BranchRules::protected),
+        None => BranchRules::unprotected(),
+    };
+
+    BranchPolicy {
+        branch: branch.to_string(),
+        pattern,
+        rules,
+    }
+}
+
+/// Matches branch names against patterns where `*` stays within one path
+/// segment, `**` crosses `/`, and `?` matches a single non-`/` character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match_from(&pattern, &text)
+}
+
+fn glob_match_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some('*') if pattern.get(1) == Some(&'*') => {
+            let rest = &pattern[2..];
+            (0..=text.len()).any(|skip| glob_match_from(rest, &text[skip..]))
+        }
+        Some('*') => {
+            let rest = &pattern[1..];
+            for skip in 0..=text.len() {
+                if glob_match_from(rest, &text[skip..]) {
+                    return true;
+                }
+                if text.get(skip) == Some(&'/') {
+                    break;
+                }
+            }
+            false
+        }
+        Some('?') => {
+            matches!(text.first(), Some(ch) if *ch != '/') && glob_match_from(&pattern[1..], &text[1..])
+        }
+        Some(ch) => text.first() == Some(ch) && glob_match_from(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Action, evaluate_with, glob_match};
+    use crate::config::{MergeStrategy, WgitConfig};
+
+    #[test]
+    fn defaults_protect_main_and_master_alike() {
+        let cfg = WgitConfig::default();
+        for branch in ["main", "master"] {
+            let policy = evaluate_with(&cfg, branch);
+            assert!(policy.denies(Action::Commit).is_some());
+            assert!(policy.denies(Action::ForcePush).is_some());
+            assert!(policy.rules.require_tag_on_finish);
+            assert_eq!(policy.rules.merge_strategies, vec![MergeStrategy::Squash]);
+        }
+        assert!(!evaluate_with(&cfg, "develop").is_protected());
+    }
+
+    #[test]
+    fn exact_names_match_only_themselves() {
+        assert!(glob_match("main", "main"));
+        assert!(!glob_match("main", "main2"));
+        assert!(!glob_match("main", "feature/main"));
+    }
+
+    #[test]
+    fn single_star_stays_within_a_segment() {
+        assert!(glob_match("release/*", "release/1.2"));
+        assert!(glob_match("release/*", "release/"));
+        assert!(!glob_match("release/*", "release/1.2/hotfix"));
+        assert!(!glob_match("release/*", "releases/1.2"));
+        assert!(glob_match("*-stable", "v2-stable"));
+        assert!(!glob_match("*-stable", "team/v2-stable"));
+    }
+
+    #[test]
+    fn double_star_crosses_segments() {
+        assert!(glob_match("team/**", "team/a/b/c"));
+        assert!(glob_match("**/main", "mirror/upstream/main"));
+        assert!(glob_match("**", "any/branch"));
+        assert!(!glob_match("team/**", "other/a"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_non_slash_character() {
+        assert!(glob_match("v?", "v1"));
+        assert!(!glob_match("v?", "v10"));
+        assert!(!glob_match("a?b", "a/b"));
+    }
+}
@@ -0,0 +1,912 @@
+use crate::config::{VersionFile, VersionFormat};
+use crate::git::CommitEntry;
+use crate::policy;
+use anyhow::{Context, Result, anyhow, bail};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const CHECKSUMS_NAME: &str = "SHA256SUMS";
+pub const SIGNATURE_NAME: &str = "SHA256SUMS.sig";
+
+/// One rewritten line, shown in the preview before anything is written.
+pub struct VersionEdit {
+    pub path: String,
+    /// 1-based line number.
+    pub line: usize,
+    pub before: String,
+    pub after: String,
+}
+
+/// New file contents plus the line edits that produce them.
+pub struct VersionBump {
+    pub files: Vec<(PathBuf, String)>,
+    pub edits: Vec<VersionEdit>,
+}
+
+impl VersionBump {
+    /// Paths to stage, relative to the repository root.
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = self.edits.iter().map(|edit| edit.path.clone()).collect();
+        paths.dedup();
+        paths
+    }
+}
+
+/// Works out every edit needed to set `version` in `files`. Only the
+/// version string changes; quoting, spacing and comments are kept.
+pub fn plan_version_bump(
+    repo_root: &Path,
+    files: &[VersionFile],
+    version: &str,
+) -> Result<VersionBump> {
+    let mut bump = VersionBump {
+        files: Vec::new(),
+        edits: Vec::new(),
+    };
+    for file in files {
+        let full = repo_root.join(&file.path);
+        // Already planned through a workspace member that inherits its version.
+        if bump.files.iter().any(|(path, _)| *path == full) {
+            continue;
+        }
+        let content = fs::read_to_string(&full)
+            .with_context(|| format!("failed to read version file: {}", file.path))?;
+        let (line, before, after) = match file.format {
+            VersionFormat::Cargo => {
+                let found = toml_version_line(&content, &["package", "workspace.package"]);
+                let Some((line, before, after)) = found.map(|line| bump_line(line, version)) else {
+                    if inherits_workspace_version(&content) {
+                        plan_inherited_version(
+                            &mut bump, repo_root, &file.path, &content, version,
+                        )?;
+                        continue;
+                    }
+                    bail!(
+                        "no `version = \"...\"` in [package] or [workspace.package] of {}",
+                        file.path
+                    );
+                };
+                if let Some(name) = cargo_package_name(&content)
+                    && let Some(lock) = cargo_lock_for(repo_root, &file.path)
+                {
+                    let old = version_in(&before);
+                    push_lock_edit(&mut bump, repo_root, &lock, &name, &old, version)?;
+                }
+                (line, before, after)
+            }
+            VersionFormat::PyProject => {
+                let found = toml_version_line(&content, &["project", "tool.poetry"]);
+                let Some(found) = found.map(|line| bump_line(line, version)) else {
+                    bail!(
+                        "no `version = \"...\"` in [project] or [tool.poetry] of {}",
+                        file.path
+                    );
+                };
+                found
+            }
+            VersionFormat::PackageJson => {
+                let Some(found) = json_version_line(&content).map(|line| bump_line(line, version))
+                else {
+                    bail!("no top-level \"version\" key in {}", file.path);
+                };
+                let dir = Path::new(&file.path).parent().unwrap_or(Path::new(""));
+                let lock = dir.join("package-lock.json");
+                if repo_root.join(&lock).is_file() {
+                    push_package_lock_edits(
+                        &mut bump,
+                        repo_root,
+                        &lock.to_string_lossy(),
+                        version,
+                    )?;
+                }
+                found
+            }
+            VersionFormat::Plain => {
+                let Some((line, text)) = content
+                    .lines()
+                    .enumerate()
+                    .find(|(_, text)| !text.trim().is_empty())
+                else {
+                    bail!("version file {} is empty", file.path);
+                };
+                let indent = &text[..text.len() - text.trim_start().len()];
+                (line, text.to_string(), format!("{indent}{version}"))
+            }
+        };
+        let new_content = replace_line(&content, line, &after);
+        bump.edits.push(VersionEdit {
+            path: file.path.clone(),
+            line: line + 1,
+            before,
+            after,
+        });
+        bump.files.push((full, new_content));
+    }
+    bump.edits.sort_by(|left, right| left.path.cmp(&right.path));
+    Ok(bump)
+}
+
+pub fn apply_version_bump(bump: &VersionBump) -> Result<()> {
+    for (path, content) in &bump.files {
+        fs::write(path, content)
+            .with_context(|| format!("failed to write version file: {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Markdown notes for `tag`, grouped by conventional commit type. Release
+/// commits written by `finish` are left out.
+pub fn release_notes(tag: &str, previous: Option<&str>, commits: &[CommitEntry]) -> String {
+    let pattern =
+        Regex::new(r"^([A-Za-z]+)(\([^)]*\))?(!)?:\s*(.+)$").expect("subject regex is valid");
+    let mut features = Vec::new();
+    let mut fixes = Vec::new();
+    let mut other = Vec::new();
+    for commit in commits {
+        let short = &commit.hash[..commit.hash.len().min(7)];
+        let Some(caps) = pattern.captures(&commit.subject) else {
+            other.push(format!("- {} ({short})", commit.subject));
+            continue;
+        };
+        let commit_type = caps[1].to_ascii_lowercase();
+        if commit_type == "chore"
+            && caps
+                .get(2)
+                .is_some_and(|scope| scope.as_str() == "(release)")
+        {
+            continue;
+        }
+        let scope = caps
+            .get(2)
+            .map(|scope| format!("**{}:** ", scope.as_str().trim_matches(['(', ')'])))
+            .unwrap_or_default();
+        let breaking = if caps.get(3).is_some() {
+            " **BREAKING**"
+        } else {
+            ""
+        };
+        let line = format!("- {scope}{}{breaking} ({short})", &caps[4]);
+        match commit_type.as_str() {
+            "feat" => features.push(line),
+            "fix" => fixes.push(line),
+            _ => other.push(line),
+        }
+    }
+
+    let mut notes = String::new();
+    for (title, lines) in [
+        ("Features", features),
+        ("Fixes", fixes),
+        ("Other changes", other),
+    ] {
+        if lines.is_empty() {
+            continue;
+        }
+        notes.push_str(&format!("## {title}\n\n{}\n\n", lines.join("\n")));
+    }
+    if notes.is_empty() {
+        notes.push_str("No changes since the previous release.\n\n");
+    }
+    match previous {
+        Some(previous) => notes.push_str(&format!("**Full changelog:** {previous}...{tag}\n")),
+        None => notes.push_str(&format!("First release: {tag}\n")),
+    }
+    notes
+}
+
+/// Whether `subject` was written by `finish` or `solve` for a merge.
+pub fn is_merge_subject(subject: &str) -> bool {
+    subject.starts_with("merge:") || subject.starts_with("merge(")
+}
+
+/// Subjects `finish` listed under `Squashed commits:` in a squash merge body.
+pub fn squashed_subjects(message: &str) -> Vec<String> {
+    message
+        .lines()
+        .skip_while(|line| line.trim() != "Squashed commits:")
+        .skip(1)
+        .map_while(|line| line.trim().strip_prefix("- "))
+        .map(|subject| subject.trim().to_string())
+        .filter(|subject| !subject.is_empty())
+        .collect()
+}
+
+/// Expands `release.assets` entries. `*` and `?` match within the file
+/// name only; every entry must match at least one file.
+pub fn resolve_assets(repo_root: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut assets = Vec::new();
+    for pattern in patterns {
+        let relative = Path::new(pattern);
+        let name = relative
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default();
+        if !name.contains(['*', '?']) {
+            let full = repo_root.join(relative);
+            if !full.is_file() {
+                bail!("release asset not found: {pattern}. build it first or fix release.assets");
+            }
+            assets.push(full);
+            continue;
+        }
+        let dir = repo_root.join(relative.parent().unwrap_or(Path::new("")));
+        let entries = fs::read_dir(&dir)
+            .with_context(|| format!("failed to read asset directory for `{pattern}`"))?;
+        let mut matched: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|file| file.to_str())
+                    .is_some_and(|file| policy::glob_match(name, file))
+            })
+            .collect();
+        if matched.is_empty() {
+            bail!(
+                "release asset pattern `{pattern}` matched no files. build first or fix release.assets"
+            );
+        }
+        matched.sort();
+        assets.append(&mut matched);
+    }
+    assets.dedup();
+    Ok(assets)
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// `sha256sum`-compatible listing: `<hex>  <file name>` per line.
+pub fn sha256sums(files: &[(String, Vec<u8>)]) -> String {
+    files
+        .iter()
+        .map(|(name, bytes)| format!("{}  {name}\n", sha256_hex(bytes)))
+        .collect()
+}
+
+/// Parses `sha256sum` output: `<hex>  <name>`, or `<hex> *<name>` for binary mode.
+pub fn parse_sha256sums(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .filter_map(|line| line.split_once(char::is_whitespace))
+        .map(|(hash, name)| {
+            let name = name.trim_start().trim_start_matches('*').trim_end();
+            (name.to_string(), hash.to_ascii_lowercase())
+        })
+        .collect()
+}
+
+/// Checks `bytes` against the `SHA256SUMS` entry for `name`.
+pub fn verify_checksum(sums: &str, name: &str, bytes: &[u8]) -> Result<()> {
+    let Some((_, expected)) = parse_sha256sums(sums)
+        .into_iter()
+        .find(|(entry, _)| entry == name)
+    else {
+        bail!("checksum check failed: SHA256SUMS has no entry for `{name}`");
+    };
+    let actual = sha256_hex(bytes);
+    if expected != actual {
+        bail!(
+            "checksum check failed for `{name}`: expected {expected}, downloaded file is {actual}"
+        );
+    }
+    Ok(())
+}
+
+/// Verifies a hex ed25519 `signature` over `message` with a hex public key.
+pub fn verify_signature(public_key: &str, message: &[u8], signature: &str) -> Result<()> {
+    let Some(key) = decode_hex::<32>(public_key) else {
+        bail!("signature check failed: embedded public key is not 64 hex characters");
+    };
+    let key = VerifyingKey::from_bytes(&key)
+        .map_err(|_| anyhow!("signature check failed: embedded public key is invalid"))?;
+    let Some(signature) = decode_hex::<64>(signature) else {
+        bail!(
+            "signature check failed: SHA256SUMS.sig is not a 128 hex character ed25519 signature"
+        );
+    };
+    key.verify_strict(message, &Signature::from_bytes(&signature))
+        .map_err(|_| anyhow!("signature check failed: SHA256SUMS.sig does not match SHA256SUMS"))
+}
+
+/// Signs `message` with a hex ed25519 secret key and returns the hex signature.
+pub fn sign(secret_key: &str, message: &[u8]) -> Result<String> {
+    let Some(seed) = decode_hex::<32>(secret_key) else {
+        bail!("signing key must be 64 hex characters (an ed25519 secret key)");
+    };
+    let signature = SigningKey::from_bytes(&seed).sign(message);
+    Ok(signature
+        .to_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
+fn decode_hex<const N: usize>(text: &str) -> Option<[u8; N]> {
+    let text = text.trim();
+    if text.len() != N * 2 || !text.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0u8; N];
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[index * 2..index * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+fn version_regex() -> Regex {
+    Regex::new(r#"^(\s*"?version"?\s*[=:]\s*)(["'])([^"']*)(["'].*)$"#)
+        .expect("version regex is valid")
+}
+
+/// First `version = "..."` line inside one of `sections`.
+fn toml_version_line<'a>(content: &'a str, sections: &[&str]) -> Option<(usize, &'a str)> {
+    let pattern = version_regex();
+    let mut in_section = false;
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            let name = trimmed.trim_matches(|ch| ch == '[' || ch == ']').trim();
+            in_section = sections.contains(&name);
+            continue;
+        }
+        if in_section && pattern.is_match(line) {
+            return Some((index, line));
+        }
+    }
+    None
+}
+
+/// `"version"` at nesting depth 1, so dependency entries never match.
+fn json_version_line(content: &str) -> Option<(usize, &str)> {
+    let pattern = version_regex();
+    let mut depth = 0usize;
+    for (index, line) in content.lines().enumerate() {
+        if depth == 1 && line.trim_start().starts_with("\"version\"") && pattern.is_match(line) {
+            return Some((index, line));
+        }
+        depth = json_depth_after(line, depth);
+    }
+    None
+}
+
+/// `packages[""].version` in a `package-lock.json`, the lock's copy of the
+/// root package's own version.
+fn json_root_package_version_line(content: &str) -> Option<(usize, &str)> {
+    let pattern = version_regex();
+    let mut depth = 0usize;
+    let mut in_packages = false;
+    let mut in_root = false;
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        match depth {
+            1 => in_packages = trimmed.starts_with("\"packages\""),
+            2 if in_packages => in_root = trimmed.starts_with("\"\""),
+            3 if in_packages
+                && in_root
+                && trimmed.starts_with("\"version\"")
+                && pattern.is_match(line) =>
+            {
+                return Some((index, line));
+            }
+            _ => {}
+        }
+        depth = json_depth_after(line, depth);
+    }
+    None
+}
+
+/// Nesting depth after `line`, counting brackets outside strings.
+fn json_depth_after(line: &str, mut depth: usize) -> usize {
+    let mut in_string = false;
+    let mut escaped = false;
+    for ch in line.chars() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '{' | '[' if !in_string => depth += 1,
+            '}' | ']' if !in_string => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    depth
+}
+
+fn bump_line((index, line): (usize, &str), version: &str) -> (usize, String, String) {
+    let after = version_regex()
+        .replace(line, |caps: &regex::Captures| {
+            format!("{}{}{version}{}", &caps[1], &caps[2], &caps[4])
+        })
+        .to_string();
+    (index, line.to_string(), after)
+}
+
+fn version_in(line: &str) -> String {
+    version_regex()
+        .captures(line)
+        .map(|caps| caps[3].to_string())
+        .unwrap_or_default()
+}
+
+fn cargo_package_name(content: &str) -> Option<String> {
+    let pattern = Regex::new(r#"^\s*name\s*=\s*"([^"]+)""#).expect("name regex is valid");
+    let mut in_package = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = trimmed == "[package]";
+            continue;
+        }
+        if in_package && let Some(caps) = pattern.captures(line) {
+            return Some(caps[1].to_string());
+        }
+    }
+    None
+}
+
+/// `version.workspace = true` (or `version = { workspace = true }`) in [package].
+fn inherits_workspace_version(content: &str) -> bool {
+    let pattern =
+        Regex::new(r"^\s*version\s*(\.\s*workspace\s*=\s*true|=\s*\{[^}]*workspace\s*=\s*true)")
+            .expect("inherited version regex is valid");
+    let mut in_package = false;
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_package = trimmed == "[package]";
+            continue;
+        }
+        if in_package && pattern.is_match(line) {
+            return true;
+        }
+    }
+    false
+}
+
+/// A member that inherits its version is bumped through the nearest
+/// `[workspace.package]` above it; only its `Cargo.lock` entry is its own.
+fn plan_inherited_version(
+    bump: &mut VersionBump,
+    repo_root: &Path,
+    manifest: &str,
+    content: &str,
+    version: &str,
+) -> Result<()> {
+    let workspace = Path::new(manifest)
+        .parent()
+        .into_iter()
+        .flat_map(Path::ancestors)
+        .skip(1)
+        .map(|dir| dir.join("Cargo.toml"))
+        .find_map(|path| {
+            let text = fs::read_to_string(repo_root.join(&path)).ok()?;
+            toml_version_line(&text, &["workspace.package"])?;
+            Some((path.to_string_lossy().to_string(), text))
+        });
+    let Some((path, text)) = workspace else {
+        bail!(
+            "{manifest} uses `version.workspace = true`, but no [workspace.package] version was found above it"
+        );
+    };
+    let found = toml_version_line(&text, &["workspace.package"]).expect("checked above");
+    let (line, before, after) = bump_line(found, version);
+    let old = version_in(&before);
+    let full = repo_root.join(&path);
+    if !bump.files.iter().any(|(planned, _)| *planned == full) {
+        let new_content = replace_line(&text, line, &after);
+        bump.edits.push(VersionEdit {
+            path: path.clone(),
+            line: line + 1,
+            before,
+            after,
+        });
+        bump.files.push((full, new_content));
+        if let Some(name) = cargo_package_name(&text)
+            && let Some(lock) = cargo_lock_for(repo_root, &path)
+        {
+            push_lock_edit(bump, repo_root, &lock, &name, &old, version)?;
+        }
+    }
+    if let Some(name) = cargo_package_name(content)
+        && let Some(lock) = cargo_lock_for(repo_root, manifest)
+    {
+        push_lock_edit(bump, repo_root, &lock, &name, &old, version)?;
+    }
+    Ok(())
+}
+
+/// `Cargo.lock` next to the manifest, else the workspace one at the root.
+fn cargo_lock_for(repo_root: &Path, manifest: &str) -> Option<String> {
+    let dir = Path::new(manifest).parent().unwrap_or(Path::new(""));
+    [dir.join("Cargo.lock"), PathBuf::from("Cargo.lock")]
+        .into_iter()
+        .find(|lock| repo_root.join(lock).is_file())
+        .map(|lock| lock.to_string_lossy().to_string())
+}
+
+/// Updates the `[[package]]` entry for `name` at `old` in `Cargo.lock`, so
+/// the release commit does not leave the lockfile stale.
+fn push_lock_edit(
+    bump: &mut VersionBump,
+    repo_root: &Path,
+    lock: &str,
+    name: &str,
+    old: &str,
+    version: &str,
+) -> Result<()> {
+    let full = repo_root.join(lock);
+    let pending = bump
+        .files
+        .iter()
+        .position(|(path, _)| *path == full)
+        .map(|index| bump.files.remove(index).1);
+    let content = match pending {
+        Some(content) => content,
+        None => {
+            fs::read_to_string(&full).with_context(|| format!("failed to read lockfile: {lock}"))?
+        }
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let name_line = format!("name = \"{name}\"");
+    let version_line = format!("version = \"{old}\"");
+    let found = lines
+        .windows(2)
+        .position(|pair| pair[0].trim() == name_line && pair[1].trim() == version_line);
+    let Some(index) = found.map(|index| index + 1) else {
+        bump.files.push((full, content));
+        return Ok(());
+    };
+    let after = format!("version = \"{version}\"");
+    let new_content = replace_line(&content, index, &after);
+    bump.edits.push(VersionEdit {
+        path: lock.to_string(),
+        line: index + 1,
+        before: lines[index].to_string(),
+        after,
+    });
+    bump.files.push((full, new_content));
+    Ok(())
+}
+
+/// Sets the top-level `"version"` and `packages[""].version` of the
+/// `package-lock.json` next to a bumped `package.json`, so the release
+/// commit does not leave the lockfile stale.
+fn push_package_lock_edits(
+    bump: &mut VersionBump,
+    repo_root: &Path,
+    lock: &str,
+    version: &str,
+) -> Result<()> {
+    let full = repo_root.join(lock);
+    if bump.files.iter().any(|(path, _)| *path == full) {
+        return Ok(());
+    }
+    let mut content =
+        fs::read_to_string(&full).with_context(|| format!("failed to read lockfile: {lock}"))?;
+    for find in [json_version_line, json_root_package_version_line] {
+        let Some((line, before, after)) = find(&content).map(|line| bump_line(line, version))
+        else {
+            continue;
+        };
+        content = replace_line(&content, line, &after);
+        bump.edits.push(VersionEdit {
+            path: lock.to_string(),
+            line: line + 1,
+            before,
+            after,
+        });
+    }
+    bump.files.push((full, content));
+    Ok(())
+}
+
+fn replace_line(content: &str, index: usize, replacement: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    for (current, line) in content.split_inclusive('\n').enumerate() {
+        if current == index {
+            let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+            result.push_str(replacement);
+            result.push_str(ending);
+        } else {
+            result.push_str(line);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8032 test vector 1.
+    const SECRET_KEY: &str = "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60";
+    const PUBLIC_KEY: &str = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+
+    /// Writes `files` into a fresh directory under the system temp dir.
+    fn fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("wgit-release-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        for (path, content) in files {
+            let full = root.join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, content).unwrap();
+        }
+        root
+    }
+
+    fn version_file(path: &str, format: VersionFormat) -> VersionFile {
+        VersionFile {
+            path: path.to_string(),
+            format,
+        }
+    }
+
+    fn sums() -> String {
+        sha256sums(&[
+            (
+                "wgit-x86_64-unknown-linux-gnu.tar.gz".to_string(),
+                b"archive".to_vec(),
+            ),
+            ("wgit-aarch64-apple-darwin".to_string(), b"binary".to_vec()),
+        ])
+    }
+
+    #[test]
+    fn signed_sums_verify() {
+        let sums = sums();
+        let signature = sign(SECRET_KEY, sums.as_bytes()).unwrap();
+        verify_signature(PUBLIC_KEY, sums.as_bytes(), &signature).unwrap();
+        verify_checksum(&sums, "wgit-aarch64-apple-darwin", b"binary").unwrap();
+        verify_checksum(&sums, "wgit-x86_64-unknown-linux-gnu.tar.gz", b"archive").unwrap();
+    }
+
+    #[test]
+    fn tampered_sums_fail_the_signature() {
+        let sums = sums();
+        let signature = sign(SECRET_KEY, sums.as_bytes()).unwrap();
+        let tampered = sums.replacen('a', "b", 1);
+        let error = verify_signature(PUBLIC_KEY, tampered.as_bytes(), &signature).unwrap_err();
+        assert!(error.to_string().contains("does not match"));
+        assert!(verify_signature(PUBLIC_KEY, sums.as_bytes(), "abcd").is_err());
+    }
+
+    #[test]
+    fn tampered_download_fails_the_checksum() {
+        let error = verify_checksum(&sums(), "wgit-aarch64-apple-darwin", b"binarY").unwrap_err();
+        assert!(error.to_string().contains("expected"));
+    }
+
+    #[test]
+    fn missing_entry_fails_the_checksum() {
+        let error =
+            verify_checksum(&sums(), "wgit-x86_64-pc-windows-msvc.zip", b"binary").unwrap_err();
+        assert!(error.to_string().contains("no entry"));
+    }
+
+    #[test]
+    fn binary_mode_lines_are_parsed() {
+        let hash = sha256_hex(b"binary");
+        let text = format!("{}  *wgit.zip\r\n{hash} *wgit\n", hash.to_ascii_uppercase());
+        assert_eq!(
+            parse_sha256sums(&text),
+            vec![
+                ("wgit.zip".to_string(), hash.clone()),
+                ("wgit".to_string(), hash.clone()),
+            ]
+        );
+        verify_checksum(&text, "wgit", b"binary").unwrap();
+        verify_checksum(&text, "wgit.zip", b"binary").unwrap();
+    }
+
+    #[test]
+    fn package_json_bump_updates_its_lockfile() {
+        let lock = r#"{
+  "name": "web",
+  "version": "1.0.0",
+  "lockfileVersion": 3,
+  "packages": {
+    "": {
+      "name": "web",
+      "version": "1.0.0",
+      "dependencies": { "left-pad": "1.0.0" }
+    },
+    "node_modules/left-pad": {
+      "version": "1.0.0"
+    }
+  }
+}
+"#;
+        let root = fixture(
+            "package-lock",
+            &[
+                (
+                    "web/package.json",
+                    "{\n  \"name\": \"web\",\n  \"version\": \"1.0.0\"\n}\n",
+                ),
+                ("web/package-lock.json", lock),
+            ],
+        );
+        let bump = plan_version_bump(
+            &root,
+            &[version_file("web/package.json", VersionFormat::PackageJson)],
+            "1.1.0",
+        )
+        .unwrap();
+        assert_eq!(
+            bump.paths(),
+            vec!["web/package-lock.json", "web/package.json"]
+        );
+        let lines: Vec<usize> = bump.edits.iter().map(|edit| edit.line).collect();
+        assert_eq!(lines, vec![3, 8, 3]);
+        let (_, content) = bump
+            .files
+            .iter()
+            .find(|(path, _)| path.ends_with("package-lock.json"))
+            .unwrap();
+        assert_eq!(content, &lock.replacen("1.0.0", "1.1.0", 2));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn cargo_bump_updates_the_manifest_and_its_lock_entry() {
+        let root = fixture(
+            "cargo",
+            &[
+                (
+                    "Cargo.toml",
+                    "[package]\nname = \"demo\"\nversion = \"0.1.0\" # keep\n\n[dependencies]\nfoo = { version = \"0.1.0\" }\n",
+                ),
+                (
+                    "Cargo.lock",
+                    "[[package]]\nname = \"demo\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"foo\"\nversion = \"0.1.0\"\n",
+                ),
+            ],
+        );
+        let bump = plan_version_bump(
+            &root,
+            &[version_file("Cargo.toml", VersionFormat::Cargo)],
+            "0.2.0",
+        )
+        .unwrap();
+        assert_eq!(bump.paths(), vec!["Cargo.lock", "Cargo.toml"]);
+        let edits: Vec<(usize, &str)> = bump
+            .edits
+            .iter()
+            .map(|edit| (edit.line, edit.after.as_str()))
+            .collect();
+        assert_eq!(
+            edits,
+            vec![
+                (3, "version = \"0.2.0\""),
+                (3, "version = \"0.2.0\" # keep")
+            ]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn inherited_version_bumps_the_workspace_package() {
+        let root = fixture(
+            "workspace",
+            &[
+                (
+                    "Cargo.toml",
+                    "[workspace]\nmembers = [\"crates/app\"]\n\n[workspace.package]\nversion = \"1.0.0\"\n",
+                ),
+                (
+                    "crates/app/Cargo.toml",
+                    "[package]\nname = \"app\"\nversion.workspace = true\n",
+                ),
+                (
+                    "Cargo.lock",
+                    "[[package]]\nname = \"app\"\nversion = \"1.0.0\"\n",
+                ),
+            ],
+        );
+        let bump = plan_version_bump(
+            &root,
+            &[version_file("crates/app/Cargo.toml", VersionFormat::Cargo)],
+            "1.1.0",
+        )
+        .unwrap();
+        assert_eq!(bump.paths(), vec!["Cargo.lock", "Cargo.toml"]);
+        assert!(
+            bump.edits
+                .iter()
+                .all(|edit| edit.after == "version = \"1.1.0\"")
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn pyproject_and_plain_files_keep_their_formatting() {
+        let root = fixture(
+            "formats",
+            &[
+                (
+                    "pyproject.toml",
+                    "[build-system]\nrequires = [\"poetry\"]\n\n[tool.poetry]\nversion = '1.0.0'\n",
+                ),
+                ("VERSION", "\n  1.0.0\r\n"),
+            ],
+        );
+        let bump = plan_version_bump(
+            &root,
+            &[
+                version_file("pyproject.toml", VersionFormat::PyProject),
+                version_file("VERSION", VersionFormat::Plain),
+            ],
+            "1.0.1",
+        )
+        .unwrap();
+        let contents: Vec<&str> = bump
+            .files
+            .iter()
+            .map(|(_, content)| content.as_str())
+            .collect();
+        assert_eq!(
+            contents,
+            vec![
+                "[build-system]\nrequires = [\"poetry\"]\n\n[tool.poetry]\nversion = '1.0.1'\n",
+                "\n  1.0.1\r\n",
+            ]
+        );
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn nested_json_versions_are_not_the_package_version() {
+        let root = fixture(
+            "json",
+            &[(
+                "package.json",
+                "{\n  \"engines\": {\n    \"version\": \"18\"\n  }\n}\n",
+            )],
+        );
+        let error = plan_version_bump(
+            &root,
+            &[version_file("package.json", VersionFormat::PackageJson)],
+            "1.0.0",
+        )
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("no top-level \"version\""));
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn release_notes_group_commits_by_type() {
+        let commits: Vec<CommitEntry> = [
+            ("1111111aaaa", "feat(cli): add status"),
+            ("2222222bbbb", "fix!: drop the old flag"),
+            ("3333333cccc", "docs: explain flows"),
+            ("4444444dddd", "chore(release): v1.1.0"),
+            ("5555555eeee", "Update README"),
+        ]
+        .into_iter()
+        .map(|(hash, subject)| CommitEntry {
+            hash: hash.to_string(),
+            subject: subject.to_string(),
+        })
+        .collect();
+        assert_eq!(
+            release_notes("v1.1.0", Some("v1.0.0"), &commits),
+            "## Features\n\n- **cli:** add status (1111111)\n\n\
+             ## Fixes\n\n- drop the old flag **BREAKING** (2222222)\n\n\
+             ## Other changes\n\n- explain flows (3333333)\n- Update README (5555555)\n\n\
+             **Full changelog:** v1.0.0...v1.1.0\n"
+        );
+        assert_eq!(
+            release_notes("v0.1.0", None, &commits[3..4]),
+            "No changes since the previous release.\n\nFirst release: v0.1.0\n"
+        );
+    }
+}
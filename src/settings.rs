@@ -0,0 +1,496 @@
+use crate::config::{self, ConfigLayer};
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, Table, TableLike, Value};
+
+/// Current config format. Version 1 files have no `version` key and were
+/// written by `wgit init` before config was layered.
+pub const CONFIG_VERSION: i64 = 2;
+
+#[derive(Clone, Copy, Debug)]
+pub enum SettingKind {
+    Bool,
+    Integer,
+    Text,
+    List,
+    Choice(&'static [&'static str]),
+}
+
+impl SettingKind {
+    pub fn describe(self) -> String {
+        match self {
+            Self::Bool => "true or false".to_string(),
+            Self::Integer => "whole number".to_string(),
+            Self::Text => "text".to_string(),
+            Self::List => "list of text: `a, b` or [\"a\", \"b\"]".to_string(),
+            Self::Choice(choices) => format!("one of {}", choices.join(", ")),
+        }
+    }
+
+    /// Parses user input into the TOML value written to a config file.
+    pub fn parse(self, key: &str, input: &str) -> Result<Value> {
+        let input = input.trim();
+        match self {
+            Self::Bool => match input {
+                "true" => Ok(Value::from(true)),
+                "false" => Ok(Value::from(false)),
+                _ => bail!("`{key}` expects true or false, got `{input}`"),
+            },
+            Self::Integer => match input.parse::<i64>() {
+                Ok(number) if number >= 0 => Ok(Value::from(number)),
+                _ => bail!("`{key}` expects a whole number, got `{input}`"),
+            },
+            Self::Text => {
+                if input.is_empty() {
+                    bail!("`{key}` cannot be empty; use `wgit config unset {key}` instead");
+                }
+                Ok(Value::from(input))
+            }
+            Self::List => {
+                let items: Vec<String> = if input.starts_with('[') {
+                    let doc = format!("value = {input}")
+                        .parse::<DocumentMut>()
+                        .with_context(|| format!("`{key}` expects a TOML list, got `{input}`"))?;
+                    let Some(array) = doc["value"].as_array() else {
+                        bail!("`{key}` expects a list, got `{input}`");
+                    };
+                    array
+                        .iter()
+                        .map(|item| {
+                            item.as_str().map(str::to_string).with_context(|| {
+                                format!("`{key}` expects a list of text, got `{item}`")
+                            })
+                        })
+                        .collect::<Result<_>>()?
+                } else {
+                    input
+                        .split(',')
+                        .map(|item| item.trim().to_string())
+                        .filter(|item| !item.is_empty())
+                        .collect()
+                };
+                Ok(Value::Array(items.into_iter().collect()))
+            }
+            Self::Choice(choices) => {
+                if !choices.contains(&input) {
+                    bail!(
+                        "`{key}` expects one of {}, got `{input}`",
+                        choices.join(", ")
+                    );
+                }
+                Ok(Value::from(input))
+            }
+        }
+    }
+}
+
+/// A key `wgit config` can read and write.
+#[derive(Debug)]
+pub struct Setting {
+    /// Dotted key; `*` stands for a protected branch pattern.
+    pub key: &'static str,
+    pub kind: SettingKind,
+    pub description: &'static str,
+}
+
+const fn setting(key: &'static str, kind: SettingKind, description: &'static str) -> Setting {
+    Setting {
+        key,
+        kind,
+        description,
+    }
+}
+
+pub const SETTINGS: &[Setting] = &[
+    setting(
+        "locked",
+        SettingKind::List,
+        "Team config only: keys or tables that global and local config may not change, e.g. commit.require_signoff.",
+    ),
+    setting(
+        "safety.protected_branches",
+        SettingKind::List,
+        "Branch names or glob patterns (`release/*`, `**`) that get the protections in safety.rules.",
+    ),
+    setting(
+        "safety.auto_bootstrap_commit_on_start",
+        SettingKind::Bool,
+        "Let `wgit start` create an empty first commit in a repository without commits.",
+    ),
+    setting(
+        "safety.require_double_confirm_for_hard_reset",
+        SettingKind::Bool,
+        "Ask twice before `wgit undo` discards changes with a hard reset.",
+    ),
+    setting(
+        "safety.rules.*.allow_direct_commits",
+        SettingKind::Bool,
+        "Allow committing directly on branches matching this pattern.",
+    ),
+    setting(
+        "safety.rules.*.allow_force_push",
+        SettingKind::Bool,
+        "Allow rewriting pushed history (amend, fixup, tidy) on branches matching this pattern.",
+    ),
+    setting(
+        "safety.rules.*.require_tag_on_finish",
+        SettingKind::Bool,
+        "Make `wgit finish` tag the merge when it lands on a branch matching this pattern.",
+    ),
+    setting(
+        "safety.rules.*.merge_strategies",
+        SettingKind::List,
+        "How `wgit finish` may merge into this pattern: squash, merge.",
+    ),
+    setting(
+        "sync.refresh_strategy",
+        SettingKind::Choice(&["rebase", "merge"]),
+        "How `wgit refresh` brings parent branch commits into the current branch.",
+    ),
+    setting(
+        "prune.stale_days",
+        SettingKind::Integer,
+        "Days without commits after which `wgit prune` lists a branch as stale.",
+    ),
+    setting(
+        "branch.types",
+        SettingKind::List,
+        "Branch types offered by `wgit start`; the type becomes the branch prefix.",
+    ),
+    setting(
+        "branch.slug_pattern",
+        SettingKind::Text,
+        "Regex the part after `type/` must match.",
+    ),
+    setting(
+        "branch.max_length",
+        SettingKind::Integer,
+        "Longest branch name `wgit start` accepts.",
+    ),
+    setting(
+        "branch.require_issue_key",
+        SettingKind::Bool,
+        "Require an issue reference when starting a branch.",
+    ),
+    setting(
+        "branch.issue_key_pattern",
+        SettingKind::Text,
+        "Regex for issue keys such as PROJ-123.",
+    ),
+    setting(
+        "issue.placement",
+        SettingKind::Choice(&["trailer", "scope", "none"]),
+        "Where `wgit commit` writes the branch's issue reference.",
+    ),
+    setting(
+        "issue.trailer_key",
+        SettingKind::Text,
+        "Trailer key used when issue.placement is trailer.",
+    ),
+    setting(
+        "commit.require_signoff",
+        SettingKind::Bool,
+        "Always add Signed-off-by (DCO); the editor cannot switch it off.",
+    ),
+    setting(
+        "commit.trailers",
+        SettingKind::List,
+        "Extra trailers offered in the commit editor: `Key` or `Key: default value`.",
+    ),
+    setting(
+        "github.api_base",
+        SettingKind::Text,
+        "GitHub API root for issue titles, pull requests and releases.",
+    ),
+    setting(
+        "backport.targets",
+        SettingKind::List,
+        "Branch patterns pre-selected as `wgit backport` targets.",
+    ),
+    setting(
+        "backport.branch_prefix",
+        SettingKind::Text,
+        "Prefix for branches created by `wgit backport`.",
+    ),
+    setting(
+        "flow.model",
+        SettingKind::Choice(&["github", "gitflow"]),
+        "Branching model for release and hotfix branches.",
+    ),
+    setting(
+        "flow.primary_branch",
+        SettingKind::Text,
+        "Branch that receives releases and hotfixes; defaults to main, then master.",
+    ),
+    setting(
+        "flow.develop_branch",
+        SettingKind::Text,
+        "Integration branch under gitflow.",
+    ),
+    setting(
+        "release.version_files",
+        SettingKind::List,
+        "Files whose version `wgit finish` bumps before tagging (Cargo.toml, package.json, pyproject.toml, VERSION, *.txt).",
+    ),
+    setting(
+        "release.assets",
+        SettingKind::List,
+        "Files uploaded by `wgit release publish`; `*` matches within a file name.",
+    ),
+    setting(
+        "update.repo",
+        SettingKind::Text,
+        "GitHub `owner/name` that `wgit update` installs wgit releases from.",
+    ),
+    setting(
+        "update.base_url",
+        SettingKind::Text,
+        "Releases endpoint of a mirror serving GitHub release JSON.",
+    ),
+    setting(
+        "update.channel",
+        SettingKind::Choice(&["stable", "prerelease"]),
+        "Which releases `wgit update` considers.",
+    ),
+    setting(
+        "update.asset_template",
+        SettingKind::Text,
+        "Release asset name stem, e.g. wgit-{target}.",
+    ),
+    setting(
+        "update.check",
+        SettingKind::Bool,
+        "Print a notice after commands when a newer wgit release exists.",
+    ),
+    setting(
+        "update.check_interval_hours",
+        SettingKind::Integer,
+        "Hours between background release checks.",
+    ),
+];
+
+/// Schema entry for a split key, matching `*` against any single segment.
+pub fn find(parts: &[String]) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|setting| {
+        let pattern: Vec<&str> = setting.key.split('.').collect();
+        pattern.len() == parts.len()
+            && pattern
+                .iter()
+                .zip(parts)
+                .all(|(expected, part)| *expected == "*" || expected == part)
+    })
+}
+
+/// Sets `key` to `value`, or removes it for `None`, in the TOML file at
+/// `path`, keeping comments and layout. Returns whether the file changed.
+pub fn write_value(path: &Path, key: &[String], value: Option<Value>) -> Result<bool> {
+    let content = if path.exists() {
+        fs::read_to_string(path)
+            .with_context(|| format!("failed to read config file: {}", path.display()))?
+    } else {
+        String::new()
+    };
+    let mut doc = content
+        .parse::<DocumentMut>()
+        .with_context(|| format!("failed to parse config file: {}", path.display()))?;
+    let changed = match value {
+        Some(value) => {
+            if content.trim().is_empty() {
+                doc.insert(config::VERSION_KEY, toml_edit::value(CONFIG_VERSION));
+            }
+            set_path(doc.as_table_mut(), key, value)
+                .with_context(|| format!("failed to update {}", path.display()))?;
+            true
+        }
+        None => remove_path(doc.as_table_mut(), key),
+    };
+    if changed {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).with_context(|| {
+                format!("failed to create config directory: {}", parent.display())
+            })?;
+        }
+        fs::write(path, doc.to_string())
+            .with_context(|| format!("failed to write config file: {}", path.display()))?;
+    }
+    Ok(changed)
+}
+
+/// Whether the TOML file at `path` sets `key` itself.
+pub fn file_sets(path: &Path, key: &[String]) -> bool {
+    let Ok(doc) = fs::read_to_string(path)
+        .unwrap_or_default()
+        .parse::<DocumentMut>()
+    else {
+        return false;
+    };
+    let mut item = doc.as_item();
+    for part in key {
+        match item.get(part.as_str()) {
+            Some(child) => item = child,
+            None => return false,
+        }
+    }
+    true
+}
+
+fn set_path(table: &mut dyn TableLike, key: &[String], value: Value) -> Result<()> {
+    let Some((first, rest)) = key.split_first() else {
+        bail!("empty key");
+    };
+    if rest.is_empty() {
+        match table.get_mut(first) {
+            // Keep the key's position and surrounding comments.
+            Some(Item::Value(existing)) => {
+                let decor = existing.decor().clone();
+                *existing = value;
+                *existing.decor_mut() = decor;
+            }
+            _ => {
+                table.insert(first, Item::Value(value));
+            }
+        }
+        return Ok(());
+    }
+    let child = table.entry(first).or_insert_with(|| {
+        let mut child = Table::new();
+        child.set_implicit(true);
+        Item::Table(child)
+    });
+    let Some(child) = child.as_table_like_mut() else {
+        bail!("`{first}` holds a value, not a table");
+    };
+    set_path(child, rest, value)
+}
+
+/// Removes `key` and any tables it leaves empty.
+fn remove_path(table: &mut dyn TableLike, key: &[String]) -> bool {
+    let Some((first, rest)) = key.split_first() else {
+        return false;
+    };
+    if rest.is_empty() {
+        return table.remove(first).is_some();
+    }
+    let Some(child) = table.get_mut(first).and_then(Item::as_table_like_mut) else {
+        return false;
+    };
+    let removed = remove_path(child, rest);
+    if removed && child.is_empty() {
+        table.remove(first);
+    }
+    removed
+}
+
+/// A local config file rewritten to `CONFIG_VERSION`.
+pub struct Migrated {
+    pub from: i64,
+    pub content: String,
+    /// One line per change, shown to the user.
+    pub notes: Vec<String>,
+}
+
+/// Steps from version `n` to `n + 1`, at index `n - 1`.
+const MIGRATIONS: [fn(&mut DocumentMut) -> Vec<String>; 1] = [drop_init_template];
+const _: () = assert!(MIGRATIONS.len() as i64 + 1 == CONFIG_VERSION);
+
+/// Brings an older local config up to `CONFIG_VERSION`. Global and team
+/// files only exist since version 2, so only the local layer migrates.
+/// Files that do not parse are left for the loader to report.
+pub fn migrate(layer: ConfigLayer, content: &str) -> Result<Option<Migrated>> {
+    if layer != ConfigLayer::Local {
+        return Ok(None);
+    }
+    let Ok(mut doc) = content.parse::<DocumentMut>() else {
+        return Ok(None);
+    };
+    let from = match doc.get(config::VERSION_KEY) {
+        None => 1,
+        Some(item) => match item.as_integer() {
+            Some(version) if (1..CONFIG_VERSION).contains(&version) => version,
+            _ => return Ok(None),
+        },
+    };
+    let mut notes = Vec::new();
+    for step in &MIGRATIONS[(from - 1) as usize..] {
+        notes.extend(step(&mut doc));
+    }
+    doc.insert(config::VERSION_KEY, toml_edit::value(CONFIG_VERSION));
+    Ok(Some(Migrated {
+        from,
+        content: doc.to_string(),
+        notes,
+    }))
+}
+
+/// What version 1 `wgit init` wrote to `.git/wgit.toml`.
+const V1_INIT_TEMPLATE: &str = "[safety]\nprotected_branches = [\"main\"]\nauto_bootstrap_commit_on_start = false\nrequire_double_confirm_for_hard_reset = true\n";
+
+/// Version 1 `wgit init` copied the defaults into `.git/wgit.toml`. Once
+/// layered, those copies would shadow team and global config, so a file
+/// still holding exactly that template is emptied. Any other file was
+/// written on purpose and keeps every value.
+fn drop_init_template(doc: &mut DocumentMut) -> Vec<String> {
+    let Ok(values) = toml::from_str::<toml::Table>(&doc.to_string()) else {
+        return Vec::new();
+    };
+    let template = toml::from_str::<toml::Table>(V1_INIT_TEMPLATE).expect("valid v1 template");
+    if values != template {
+        return Vec::new();
+    }
+    let mut leaves = Vec::new();
+    config::collect_leaves(values, &mut Vec::new(), &mut leaves);
+    for (key, _) in &leaves {
+        remove_path(doc.as_table_mut(), key);
+    }
+    vec![format!(
+        "removed the unedited `wgit init` template so team and global config apply: {}",
+        leaves
+            .iter()
+            .map(|(key, _)| config::format_key(key))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn migrated(content: &str) -> Migrated {
+        migrate(ConfigLayer::Local, content).unwrap().unwrap()
+    }
+
+    #[test]
+    fn the_untouched_init_template_is_emptied() {
+        let result = migrated(V1_INIT_TEMPLATE);
+        assert_eq!(result.from, 1);
+        assert_eq!(result.content, "version = 2\n");
+        assert_eq!(result.notes.len(), 1);
+        assert!(result.notes[0].contains("safety.protected_branches"));
+    }
+
+    #[test]
+    fn edited_files_keep_values_equal_to_the_defaults() {
+        let content = "# mine\n[safety]\nprotected_branches = [\"main\"]\n\n[commit]\nrequire_signoff = false\n";
+        let result = migrated(content);
+        assert!(result.notes.is_empty());
+        assert_eq!(result.content, format!("version = 2\n{content}"));
+    }
+
+    #[test]
+    fn only_older_local_files_migrate() {
+        let content = "[commit]\nrequire_signoff = true\n";
+        for layer in [ConfigLayer::Global, ConfigLayer::Team] {
+            assert!(migrate(layer, content).unwrap().is_none());
+        }
+        for current in [
+            "version = 2\n",
+            "version = 3\n",
+            "version = \"2\"\n",
+            "[broken\n",
+        ] {
+            assert!(migrate(ConfigLayer::Local, current).unwrap().is_none());
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use crate::config::WgitConfig;
+use crate::git;
+use crate::utils::{TrailerKind, TrailerRow};
+use anyhow::{Context, Result, bail};
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const CO_AUTHOR_LIMIT: usize = 10;
+
+/// Builds the commit editor's trailers section: sign-off first, then
+/// recent authors as co-author candidates, then keys from `commit.trailers`.
+pub fn editor_rows(cwd: &Path, cfg: &WgitConfig) -> Result<Vec<TrailerRow>> {
+    let mut rows = Vec::new();
+    let identity = git::user_identity(cwd)?;
+    if let Some(identity) = &identity {
+        rows.push(TrailerRow {
+            key: "Signed-off-by".to_string(),
+            value: identity.clone(),
+            kind: TrailerKind::Toggle {
+                enabled: cfg.commit.require_signoff,
+                locked: cfg.commit.require_signoff,
+            },
+        });
+    }
+
+    let co_authors = git::recent_authors(cwd, CO_AUTHOR_LIMIT + 1)?
+        .into_iter()
+        .filter(|author| identity.as_ref() != Some(author))
+        .take(CO_AUTHOR_LIMIT);
+    for author in co_authors {
+        rows.push(TrailerRow {
+            key: "Co-authored-by".to_string(),
+            value: author,
+            kind: TrailerKind::Toggle {
+                enabled: false,
+                locked: false,
+            },
+        });
+    }
+
+    for (key, value) in &cfg.commit.trailers {
+        rows.push(TrailerRow {
+            key: key.clone(),
+            value: value.clone(),
+            kind: TrailerKind::Text,
+        });
+    }
+    Ok(rows)
+}
+
+/// Appends `trailers` to `message` through `git interpret-trailers`.
+pub fn apply(cwd: &Path, message: &str, trailers: &[(String, String)]) -> Result<String> {
+    if trailers.is_empty() {
+        return Ok(message.to_string());
+    }
+
+    let mut tmp_path = std::env::temp_dir();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .context("failed to generate temp filename")?
+        .as_nanos();
+    tmp_path.push(format!("wgit-trailers-{nanos}.txt"));
+    // Without a final newline git glues the trailers onto the last paragraph.
+    fs::write(&tmp_path, format!("{}\n", message.trim_end())).with_context(|| {
+        format!(
+            "failed to write commit message file: {}",
+            tmp_path.display()
+        )
+    })?;
+    let tmp_arg = tmp_path.to_string_lossy().to_string();
+    let result = git::interpret_trailers(cwd, &tmp_arg, trailers);
+    let _ = fs::remove_file(&tmp_path);
+    result
+}
+
+/// Trailers for commits wgit writes without the editor: the sign-off when
+/// `commit.require_signoff` enforces it.
+pub fn enforced(cwd: &Path, cfg: &WgitConfig) -> Result<Vec<(String, String)>> {
+    match git::user_identity(cwd)? {
+        Some(identity) if cfg.commit.require_signoff => {
+            Ok(vec![("Signed-off-by".to_string(), identity)])
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Fails when DCO is enforced but git has no identity to sign with.
+pub fn ensure_signoff_identity(cwd: &Path, cfg: &WgitConfig) -> Result<()> {
+    if cfg.commit.require_signoff && git::user_identity(cwd)?.is_none() {
+        bail!(
+            "commit.require_signoff is enabled but user.name/user.email are not set. run `git config user.name` and `git config user.email` first"
+        );
+    }
+    Ok(())
+}
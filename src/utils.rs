@@ -17,10 +17,36 @@ use std::io::stdout;
 use std::time::Duration;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+#[derive(Default)]
 pub struct CommitDraft {
     pub scope: String,
     pub subject: String,
     pub body: String,
+    /// Enabled `(key, value)` trailers, in the order shown in the editor.
+    pub trailers: Vec<(String, String)>,
+}
+
+/// One row of the commit editor's trailers section.
+pub struct TrailerRow {
+    pub key: String,
+    pub value: String,
+    pub kind: TrailerKind,
+}
+
+pub enum TrailerKind {
+    /// Fixed value switched on or off with Space; `locked` rows cannot be turned off.
+    Toggle { enabled: bool, locked: bool },
+    /// Free text value; left empty, the trailer is skipped.
+    Text,
+}
+
+impl TrailerRow {
+    fn is_enabled(&self) -> bool {
+        match self.kind {
+            TrailerKind::Toggle { enabled, .. } => enabled,
+            TrailerKind::Text => !self.value.trim().is_empty(),
+        }
+    }
 }
 
 const COLOR_TEXT: Color = Color::Rgb(251, 224, 195); // #fbe0c3
@@ -286,13 +312,22 @@ fn menu_style_line(raw: &str) -> Line<'static> {
 }
 
 pub fn select_one(prompt: &str, options: &[String]) -> Result<Option<usize>> {
+    select_one_preselected(prompt, options, 0)
+}
+
+/// Single select that starts with the cursor on `preselected`.
+pub fn select_one_preselected(
+    prompt: &str,
+    options: &[String],
+    preselected: usize,
+) -> Result<Option<usize>> {
     if options.is_empty() {
         return Ok(None);
     }
 
     let mut session = TuiSession::start()?;
     let mut query = String::new();
-    let mut cursor: usize = 0;
+    let mut cursor: usize = preselected.min(options.len() - 1);
 
     loop {
         let filtered = filter_indices(options, &query);
@@ -395,12 +430,25 @@ pub fn select_one(prompt: &str, options: &[String]) -> Result<Option<usize>> {
 }
 
 pub fn select_many(prompt: &str, options: &[String]) -> Result<Vec<usize>> {
+    select_many_preselected(prompt, options, &[])
+}
+
+/// Multi select that starts with `preselected` indices already checked.
+pub fn select_many_preselected(
+    prompt: &str,
+    options: &[String],
+    preselected: &[usize],
+) -> Result<Vec<usize>> {
     if options.is_empty() {
         return Ok(Vec::new());
     }
 
     let mut session = TuiSession::start()?;
-    let mut selected: BTreeSet<usize> = BTreeSet::new();
+    let mut selected: BTreeSet<usize> = preselected
+        .iter()
+        .copied()
+        .filter(|idx| *idx < options.len())
+        .collect();
     let mut query = String::new();
     let mut cursor: usize = 0;
 
@@ -544,6 +592,142 @@ pub fn select_many(prompt: &str, options: &[String]) -> Result<Vec<usize>> {
     }
 }
 
+/// Single select with a scrollable preview pane; `previews[i]` belongs to `options[i]`.
+pub fn select_one_with_preview(
+    prompt: &str,
+    options: &[String],
+    previews: &[String],
+) -> Result<Option<usize>> {
+    if options.is_empty() {
+        return Ok(None);
+    }
+
+    let mut session = TuiSession::start()?;
+    let mut query = String::new();
+    let mut cursor: usize = 0;
+    let mut preview_scroll: u16 = 0;
+
+    loop {
+        let filtered = filter_indices(options, &query);
+        if filtered.is_empty() {
+            cursor = 0;
+        } else if cursor >= filtered.len() {
+            cursor = filtered.len() - 1;
+        }
+
+        session.terminal.draw(|frame| {
+            let outer = make_list_block("wgit");
+            let inner = inset_horizontally(outer.inner(frame.area()), 2);
+            frame.render_widget(outer, frame.area());
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                    Constraint::Min(5),
+                    Constraint::Length(1),
+                ])
+                .split(inner);
+
+            let title = Paragraph::new(Line::from(prompt.to_string())).style(title_style());
+            frame.render_widget(title, chunks[0]);
+
+            let search = Paragraph::new(format!(
+                "Search: {}",
+                if query.is_empty() { "(empty)" } else { &query }
+            ))
+            .style(hint_style());
+            frame.render_widget(search, chunks[1]);
+
+            let panes = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .split(chunks[2]);
+
+            let items: Vec<ListItem> = if filtered.is_empty() {
+                vec![ListItem::new(Span::styled("No matches", hint_style()))]
+            } else {
+                filtered
+                    .iter()
+                    .map(|idx| ListItem::new(Span::styled(options[*idx].clone(), text_style())))
+                    .collect()
+            };
+            let list = List::new(items)
+                .block(make_inner_block("Options"))
+                .highlight_style(list_highlight_style());
+            let mut state = ListState::default();
+            if !filtered.is_empty() {
+                state.select(Some(cursor));
+            }
+            frame.render_stateful_widget(list, panes[0], &mut state);
+
+            let preview_text = filtered
+                .get(cursor)
+                .and_then(|idx| previews.get(*idx))
+                .cloned()
+                .unwrap_or_default();
+            let preview = Paragraph::new(preview_text)
+                .scroll((preview_scroll, 0))
+                .style(text_style())
+                .block(make_inner_block("Preview"));
+            frame.render_widget(preview, panes[1]);
+
+            let hint = Paragraph::new(format!(
+                "{}  |  Up/Down move  PgUp/PgDn scroll preview  Enter confirm  Esc cancel",
+                selected_line(filtered.len(), options.len(), &query)
+            ))
+            .style(hint_style());
+            frame.render_widget(hint, chunks[3]);
+        })?;
+
+        if !event::poll(Duration::from_millis(200))? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            match key.code {
+                KeyCode::Esc => return Ok(None),
+                KeyCode::Up => {
+                    cursor = cursor.saturating_sub(1);
+                    preview_scroll = 0;
+                }
+                KeyCode::Down => {
+                    let filtered = filter_indices(options, &query);
+                    if !filtered.is_empty() && cursor + 1 < filtered.len() {
+                        cursor += 1;
+                        preview_scroll = 0;
+                    }
+                }
+                KeyCode::PageUp => {
+                    preview_scroll = preview_scroll.saturating_sub(10);
+                }
+                KeyCode::PageDown => {
+                    preview_scroll = preview_scroll.saturating_add(10);
+                }
+                KeyCode::Enter => {
+                    let filtered = filter_indices(options, &query);
+                    if filtered.is_empty() {
+                        continue;
+                    }
+                    return Ok(Some(filtered[cursor]));
+                }
+                KeyCode::Backspace => {
+                    query.pop();
+                }
+                KeyCode::Char(c)
+                    if key.modifiers.is_empty() || key.modifiers == KeyModifiers::SHIFT =>
+                {
+                    query.push(c);
+                    cursor = 0;
+                    preview_scroll = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
 pub fn input_text(prompt: &str) -> Result<String> {
     let mut session = TuiSession::start()?;
     let mut value = String::new();
@@ -646,15 +830,117 @@ impl CommitField {
     }
 }
 
-pub fn edit_commit_message(commit_type: &str) -> Result<Option<CommitDraft>> {
+fn trailer_line(row: &TrailerRow, focused: bool) -> Line<'static> {
+    let marker = match row.kind {
+        TrailerKind::Toggle {
+            enabled: true,
+            locked: true,
+        } => "[=] ",
+        TrailerKind::Toggle { enabled: true, .. } => "[x] ",
+        TrailerKind::Toggle { enabled: false, .. } => "[ ] ",
+        TrailerKind::Text => "    ",
+    };
+    let marker_style = if row.is_enabled() {
+        Style::default()
+            .fg(COLOR_SUCCESS)
+            .add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(COLOR_BORDER)
+    };
+    let text = if focused {
+        list_highlight_style()
+    } else {
+        text_style()
+    };
+    Line::from(vec![
+        Span::styled(marker.to_string(), marker_style),
+        Span::styled(
+            format!("{}: ", row.key),
+            Style::default()
+                .fg(COLOR_WARNING)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(row.value.clone(), text),
+    ])
+}
+
+/// Applies a key press inside the trailers section. Returns `true` when
+/// Up on the first row should hand focus back to the body.
+fn edit_trailer_rows(
+    rows: &mut [TrailerRow],
+    index: &mut usize,
+    cursor: &mut usize,
+    code: KeyCode,
+    modifiers: KeyModifiers,
+) -> bool {
+    match code {
+        KeyCode::Up if *index == 0 => return true,
+        KeyCode::Up => {
+            *index -= 1;
+            *cursor = rows[*index].value.chars().count();
+            return false;
+        }
+        KeyCode::Down | KeyCode::Enter => {
+            *index = (*index + 1).min(rows.len() - 1);
+            *cursor = rows[*index].value.chars().count();
+            return false;
+        }
+        _ => {}
+    }
+
+    let row = &mut rows[*index];
+    match &mut row.kind {
+        TrailerKind::Toggle { enabled, locked } => {
+            if code == KeyCode::Char(' ') && !*locked {
+                *enabled = !*enabled;
+            }
+        }
+        TrailerKind::Text => match code {
+            KeyCode::Backspace => delete_char_before_cursor(&mut row.value, cursor),
+            KeyCode::Delete => delete_char_at_cursor(&mut row.value, *cursor),
+            KeyCode::Left => move_cursor_left(cursor),
+            KeyCode::Right => move_cursor_right(cursor, row.value.chars().count()),
+            KeyCode::Home => *cursor = 0,
+            KeyCode::End => *cursor = row.value.chars().count(),
+            KeyCode::Char(c)
+                if modifiers == KeyModifiers::NONE || modifiers == KeyModifiers::SHIFT =>
+            {
+                insert_char_at(&mut row.value, cursor, c)
+            }
+            _ => {}
+        },
+    }
+    false
+}
+
+/// Structured commit editor prefilled from `initial` (its trailers are ignored).
+/// `trailer_rows` fill the trailers section (co-authors, sign-off and
+/// configured keys); pass an empty list to hide it.
+pub fn edit_commit_message(
+    commit_type: &str,
+    initial: CommitDraft,
+    mut trailer_rows: Vec<TrailerRow>,
+) -> Result<Option<CommitDraft>> {
     let mut session = TuiSession::start()?;
-    let mut scope = String::new();
-    let mut subject = String::new();
-    let mut body = String::new();
-    let mut scope_cursor = 0usize;
-    let mut subject_cursor = 0usize;
-    let mut body_cursor = 0usize;
+    let CommitDraft {
+        mut scope,
+        mut subject,
+        mut body,
+        ..
+    } = initial;
+    let mut scope_cursor = scope.chars().count();
+    let mut subject_cursor = subject.chars().count();
+    let mut body_cursor = body.chars().count();
     let mut active = CommitField::Subject;
+    let mut trailers_focused = false;
+    let has_trailers = !trailer_rows.is_empty();
+    let mut trailer_index = 0usize;
+    let mut trailer_cursor = 0usize;
+    let trailer_height = if has_trailers {
+        trailer_rows.len().min(6) as u16 + 2
+    } else {
+        0
+    };
 
     loop {
         session.terminal.draw(|frame| {
@@ -669,6 +955,7 @@ pub fn edit_commit_message(commit_type: &str) -> Result<Option<CommitDraft>> {
                     Constraint::Length(4),
                     Constraint::Length(4),
                     Constraint::Min(7),
+                    Constraint::Length(trailer_height),
                     Constraint::Length(1),
                 ])
                 .split(inner);
@@ -716,7 +1003,8 @@ pub fn edit_commit_message(commit_type: &str) -> Result<Option<CommitDraft>> {
                 .block(subject_block);
             frame.render_widget(subject_paragraph, chunks[2]);
 
-            let body_block = make_focus_block("Body", active == CommitField::Body);
+            let body_block =
+                make_focus_block("Body", active == CommitField::Body && !trailers_focused);
             let body_inner = body_block.inner(chunks[3]);
             let (body_cursor_x, body_cursor_y) =
                 cursor_xy_with_wrap(&body, body_cursor, body_inner.width.max(1));
@@ -728,12 +1016,42 @@ pub fn edit_commit_message(commit_type: &str) -> Result<Option<CommitDraft>> {
                 .block(body_block);
             frame.render_widget(body_paragraph, chunks[3]);
 
-            let hint = Paragraph::new(
-                "Up/Down switch field  Enter next/newline  Left/Right move  Home/End line start/end",
-            )
-            .style(hint_style());
-            frame.render_widget(hint, chunks[4]);
+            let trailer_block = make_focus_block("Trailers", trailers_focused);
+            let trailer_inner = trailer_block.inner(chunks[4]);
+            let trailer_scroll_y =
+                (trailer_index as u16).saturating_sub(trailer_inner.height.saturating_sub(1));
+            if has_trailers {
+                let lines: Vec<Line> = trailer_rows
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, row)| trailer_line(row, trailers_focused && idx == trailer_index))
+                    .collect();
+                let trailer_paragraph = Paragraph::new(lines)
+                    .scroll((trailer_scroll_y, 0))
+                    .block(trailer_block);
+                frame.render_widget(trailer_paragraph, chunks[4]);
+            }
 
+            let hint_text = if trailers_focused {
+                "Up/Down move  Space toggle  type to fill a value  Esc save  Ctrl+C cancel"
+            } else {
+                "Up/Down switch field  Enter next/newline  Left/Right move  Home/End line start/end"
+            };
+            let hint = Paragraph::new(hint_text).style(hint_style());
+            frame.render_widget(hint, chunks[5]);
+
+            if trailers_focused {
+                let row = &trailer_rows[trailer_index];
+                if matches!(row.kind, TrailerKind::Text) {
+                    let x = display_width("    ")
+                        + display_width(&row.key)
+                        + display_width(": ")
+                        + display_width_before_cursor(&row.value, trailer_cursor);
+                    let y = (trailer_index as u16).saturating_sub(trailer_scroll_y);
+                    set_cursor(frame, trailer_inner, x as u16, y);
+                }
+                return;
+            }
             match active {
                 CommitField::Scope => {
                     let display_x = scope_total_x.saturating_sub(scope_scroll_x);
@@ -755,16 +1073,38 @@ pub fn edit_commit_message(commit_type: &str) -> Result<Option<CommitDraft>> {
         {
             match (code, modifiers) {
                 (KeyCode::Esc, _) => {
+                    let trailers = trailer_rows
+                        .iter()
+                        .filter(|row| row.is_enabled())
+                        .map(|row| (row.key.clone(), row.value.trim().to_string()))
+                        .collect();
                     return Ok(Some(CommitDraft {
                         scope,
                         subject,
                         body,
+                        trailers,
                     }));
                 }
                 (KeyCode::Char('c'), KeyModifiers::CONTROL) => return Ok(None),
+                _ if trailers_focused => {
+                    let leave = edit_trailer_rows(
+                        &mut trailer_rows,
+                        &mut trailer_index,
+                        &mut trailer_cursor,
+                        code,
+                        modifiers,
+                    );
+                    if leave {
+                        trailers_focused = false;
+                    }
+                }
                 (KeyCode::Up, _) => {
                     active = active.up();
                 }
+                (KeyCode::Down, _) if active == CommitField::Body && has_trailers => {
+                    trailers_focused = true;
+                    trailer_cursor = trailer_rows[trailer_index].value.chars().count();
+                }
                 (KeyCode::Down, _) => {
                     active = active.down();
                 }
@@ -828,8 +1168,320 @@ pub fn edit_commit_message(commit_type: &str) -> Result<Option<CommitDraft>> {
     }
 }
 
+/// Todo-list action for one commit in `wgit tidy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebaseAction {
+    Pick,
+    Reword,
+    Squash,
+    Fixup,
+    Drop,
+}
+
+impl RebaseAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pick => "pick",
+            Self::Reword => "reword",
+            Self::Squash => "squash",
+            Self::Fixup => "fixup",
+            Self::Drop => "drop",
+        }
+    }
+
+    /// Squash and fixup fold into the commit above, so they need one.
+    fn needs_previous(self) -> bool {
+        matches!(self, Self::Squash | Self::Fixup)
+    }
+}
+
+pub struct RebaseStep {
+    pub action: RebaseAction,
+    pub hash: String,
+    pub subject: String,
+}
+
+/// Explains why the plan cannot run, if it cannot.
+fn rebase_plan_error(steps: &[RebaseStep]) -> Option<String> {
+    let first = steps
+        .iter()
+        .find(|step| step.action != RebaseAction::Drop)?;
+    if first.action.needs_previous() {
+        return Some(format!(
+            "`{}` cannot {}: there is no earlier commit to fold into",
+            &first.hash[..first.hash.len().min(7)],
+            first.action.as_str()
+        ));
+    }
+    None
+}
+
+/// Rebase plan editor: commits are listed oldest first, like git's todo list.
+/// Returns the edited plan, or `None` when canceled.
+pub fn edit_rebase_plan(
+    prompt: &str,
+    mut steps: Vec<RebaseStep>,
+) -> Result<Option<Vec<RebaseStep>>> {
+    if steps.is_empty() {
+        return Ok(None);
+    }
+
+    let mut session = TuiSession::start()?;
+    let mut cursor: usize = 0;
+    let mut error: Option<String> = None;
+
+    loop {
+        session.terminal.draw(|frame| {
+            let outer = make_list_block("wgit");
+            let inner = inset_horizontally(outer.inner(frame.area()), 2);
+            frame.render_widget(outer, frame.area());
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(1),
+                    Constraint::Min(5),
+                    Constraint::Length(1),
+                    Constraint::Length(1),
+                ])
+                .split(inner);
+
+            let title = Paragraph::new(Line::from(prompt.to_string())).style(title_style());
+            frame.render_widget(title, chunks[0]);
+
+            let items: Vec<ListItem> = steps
+                .iter()
+                .map(|step| {
+                    let action_style = match step.action {
+                        RebaseAction::Drop => Style::default().fg(COLOR_MUTED),
+                        RebaseAction::Pick => Style::default().fg(COLOR_BORDER),
+                        _ => Style::default()
+                            .fg(COLOR_ACCENT)
+                            .add_modifier(Modifier::BOLD),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{:<7}", step.action.as_str()), action_style),
+                        Span::styled(
+                            format!("{} ", &step.hash[..step.hash.len().min(7)]),
+                            hint_style(),
+                        ),
+                        Span::styled(step.subject.clone(), text_style()),
+                    ]))
+                })
+                .collect();
+            let list = List::new(items)
+                .block(make_inner_block("Oldest first"))
+                .highlight_style(list_highlight_style());
+            let mut state = ListState::default();
+            state.select(Some(cursor));
+            frame.render_stateful_widget(list, chunks[1], &mut state);
+
+            let status = match &error {
+                Some(message) => Paragraph::new(message.clone())
+                    .style(Style::default().fg(COLOR_WARNING)),
+                None => Paragraph::new(
+                    "p pick  r reword  s squash  f fixup  d drop  |  Shift+Up/Down or K/J move commit",
+                )
+                .style(hint_style()),
+            };
+            frame.render_widget(status, chunks[2]);
+
+            let hint = Paragraph::new("Up/Down select  Enter run plan  Esc cancel")
+                .style(hint_style());
+            frame.render_widget(hint, chunks[3]);
+        })?;
+
+        if !event::poll(Duration::from_millis(200))? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            error = None;
+            let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+            let move_up = (key.code == KeyCode::Up && shift) || key.code == KeyCode::Char('K');
+            let move_down = (key.code == KeyCode::Down && shift) || key.code == KeyCode::Char('J');
+            match key.code {
+                KeyCode::Esc => return Ok(None),
+                _ if move_up && cursor > 0 => {
+                    steps.swap(cursor, cursor - 1);
+                    cursor -= 1;
+                }
+                _ if move_down && cursor + 1 < steps.len() => {
+                    steps.swap(cursor, cursor + 1);
+                    cursor += 1;
+                }
+                _ if move_up || move_down => {}
+                KeyCode::Up => {
+                    cursor = cursor.saturating_sub(1);
+                }
+                KeyCode::Down if cursor + 1 < steps.len() => {
+                    cursor += 1;
+                }
+                KeyCode::Char(c) => {
+                    let action = match c {
+                        'p' => Some(RebaseAction::Pick),
+                        'r' => Some(RebaseAction::Reword),
+                        's' => Some(RebaseAction::Squash),
+                        'f' => Some(RebaseAction::Fixup),
+                        'd' => Some(RebaseAction::Drop),
+                        _ => None,
+                    };
+                    if let Some(action) = action {
+                        steps[cursor].action = action;
+                    }
+                }
+                KeyCode::Enter => match rebase_plan_error(&steps) {
+                    Some(message) => error = Some(message),
+                    None if steps.iter().all(|step| step.action == RebaseAction::Drop) => {
+                        error = Some("Every commit is dropped. Keep at least one.".to_string());
+                    }
+                    None => return Ok(Some(steps)),
+                },
+                _ => {}
+            }
+        }
+    }
+}
+
+/// A titled list on the `wgit status` dashboard.
+pub struct DashboardPanel {
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+fn panel_paragraph(panel: &DashboardPanel, scroll: u16) -> Paragraph<'_> {
+    let lines: Vec<Line> = if panel.lines.is_empty() {
+        vec![Line::from(Span::styled("(none)", hint_style()))]
+    } else {
+        panel
+            .lines
+            .iter()
+            .map(|line| Line::from(Span::styled(line.clone(), text_style())))
+            .collect()
+    };
+    Paragraph::new(lines)
+        .scroll((scroll, 0))
+        .block(make_inner_block(&panel.title))
+}
+
+/// Read-only status screen: the summary and its warnings on top, `columns`
+/// side by side, `footer` below. Returns the shortcut key that was pressed,
+/// or `None` when closed with Esc or `q`.
+pub fn show_dashboard(
+    summary: &[String],
+    warnings: &[String],
+    columns: &[DashboardPanel],
+    footer: &DashboardPanel,
+    shortcuts: &[(char, &str)],
+) -> Result<Option<char>> {
+    let mut session = TuiSession::start()?;
+    let mut scroll: u16 = 0;
+    let longest = columns
+        .iter()
+        .map(|panel| panel.lines.len())
+        .max()
+        .unwrap_or(0);
+    let max_scroll = u16::try_from(longest.saturating_sub(1)).unwrap_or(u16::MAX);
+    let keys: String = shortcuts
+        .iter()
+        .map(|(key, label)| format!("{key} {label}"))
+        .collect::<Vec<_>>()
+        .join("  ");
+
+    loop {
+        session.terminal.draw(|frame| {
+            let outer = make_list_block("wgit");
+            let inner = inset_horizontally(outer.inner(frame.area()), 2);
+            frame.render_widget(outer, frame.area());
+
+            let summary_height = u16::try_from(summary.len() + warnings.len() + 3).unwrap_or(3);
+            let footer_height = u16::try_from(footer.lines.len().clamp(1, 5) + 3).unwrap_or(4);
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(1),
+                    Constraint::Length(summary_height),
+                    Constraint::Min(5),
+                    Constraint::Length(footer_height),
+                    Constraint::Length(1),
+                ])
+                .split(inner);
+
+            let title = Paragraph::new(Line::from("Repository status")).style(title_style());
+            frame.render_widget(title, chunks[0]);
+
+            let mut lines: Vec<Line> = summary
+                .iter()
+                .map(|line| Line::from(Span::styled(line.clone(), text_style())))
+                .collect();
+            lines.extend(warnings.iter().map(|warning| {
+                Line::from(Span::styled(
+                    format!("! {warning}"),
+                    Style::default()
+                        .fg(COLOR_WARNING)
+                        .add_modifier(Modifier::BOLD),
+                ))
+            }));
+            let summary_block =
+                Paragraph::new(lines).block(make_focus_block("Branch", !warnings.is_empty()));
+            frame.render_widget(summary_block, chunks[1]);
+
+            if !columns.is_empty() {
+                let count = u32::try_from(columns.len()).unwrap_or(1);
+                let panes = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints(columns.iter().map(|_| Constraint::Ratio(1, count)))
+                    .split(chunks[2]);
+                for (panel, area) in columns.iter().zip(panes.iter()) {
+                    frame.render_widget(panel_paragraph(panel, scroll), *area);
+                }
+            }
+            frame.render_widget(panel_paragraph(footer, 0), chunks[3]);
+
+            let hint = Paragraph::new(format!("{keys}  |  Up/Down PgUp/PgDn scroll  Esc/q close"))
+                .style(hint_style());
+            frame.render_widget(hint, chunks[4]);
+        })?;
+
+        if !event::poll(Duration::from_millis(200))? {
+            continue;
+        }
+
+        if let Event::Key(key) = event::read()? {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') => return Ok(None),
+                KeyCode::Up => scroll = scroll.saturating_sub(1),
+                KeyCode::Down => scroll = scroll.saturating_add(1).min(max_scroll),
+                KeyCode::PageUp => scroll = scroll.saturating_sub(10),
+                KeyCode::PageDown => scroll = scroll.saturating_add(10).min(max_scroll),
+                KeyCode::Char(c) if shortcuts.iter().any(|(key, _)| *key == c) => {
+                    return Ok(Some(c));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
 pub fn confirm(prompt: &str) -> Result<bool> {
     let options = vec!["yes".to_string(), "no".to_string()];
     let choice = select_one(prompt, &options)?;
     Ok(matches!(choice, Some(0)))
 }
+
+/// Outcome of the shared conflict menu used after a failed merge or rebase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictChoice {
+    Abort,
+    Continue,
+}
+
+pub fn select_conflict_option(prompt: &str) -> Result<Option<ConflictChoice>> {
+    let options = vec!["abort".to_string(), "continue".to_string()];
+    let choice = select_one(prompt, &options)?;
+    Ok(match choice {
+        Some(0) => Some(ConflictChoice::Abort),
+        Some(1) => Some(ConflictChoice::Continue),
+        _ => None,
+    })
+}